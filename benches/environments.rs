@@ -0,0 +1,102 @@
+//! Steps per second of every environment under a uniformly random policy,
+//! run with `cargo bench --bench environments`
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+use std::io::BufReader;
+use std::net::TcpListener;
+use std::thread;
+
+use reinforcement_learning::environment::continuing::Continuing;
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::history::{HistoryMode, HistoryWrapper};
+use reinforcement_learning::environment::m_wrapper::{MWrapper, MWrapperConfig};
+use reinforcement_learning::environment::memory::Register;
+use reinforcement_learning::environment::recorder::Recorder;
+use reinforcement_learning::environment::remote::Remote;
+use reinforcement_learning::environment::t_corridor::TCorridor;
+use reinforcement_learning::environment::t_maze::{TMaze, TMazeConfig};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::protocol::serve;
+use reinforcement_learning::random;
+
+const STEPS: usize = 1000;
+
+/// Takes STEPS random actions, calling reset whenever an episode ends
+fn random_steps<E: Environment>(env: &mut E, reset: &mut dyn FnMut(&mut E)) {
+    let mut rng = random::rng();
+
+    for _ in 0..STEPS {
+        let actions = env.available_actions(env.current_observation());
+        env.take_action(actions[rng.gen_range(0, actions.len())])
+            .unwrap();
+        if env.terminated() {
+            reset(env);
+        }
+    }
+}
+
+fn bench<E: Environment>(c: &mut Criterion, name: &str, new: impl Fn() -> E) {
+    let mut group = c.benchmark_group("environment_steps");
+    group.throughput(Throughput::Elements(STEPS as u64));
+    random::seed(0);
+    let mut env = new();
+    group.bench_function(name, |b| {
+        b.iter(|| random_steps(&mut env, &mut |env| *env = new()))
+    });
+    group.finish();
+}
+
+fn cliff_world() -> GridWorld {
+    GridWorld::new(cliff(12, 4))
+}
+
+fn t_maze() -> TMaze {
+    let mut config = TMazeConfig::new(10);
+    config.exits = 4;
+    config.cues = 3;
+    config.distractors = 4;
+    config.distractor_probability = 0.3;
+    TMaze::new(config).unwrap()
+}
+
+fn local_environments(c: &mut Criterion) {
+    bench(c, "gridworld_cliff", cliff_world);
+    bench(c, "t_corridor", || TCorridor::new(10));
+    bench(c, "t_maze", t_maze);
+    bench(c, "m_wrapper_t_corridor", || {
+        MWrapper::new(TCorridor::new(10))
+    });
+    bench(c, "m_wrapper_register_t_corridor", || {
+        MWrapper::with_memory(TCorridor::new(10), Register::new(4), MWrapperConfig::new())
+    });
+    bench(c, "history_t_corridor", || {
+        HistoryWrapper::<_, 2>::new(TCorridor::new(10), HistoryMode::Both)
+    });
+    bench(c, "recorder_cliff", || Recorder::new(cliff_world()));
+    bench(c, "continuing_cliff", || Continuing::new(cliff_world));
+}
+
+// includes the JSON protocol and a loopback TCP round trip per step
+fn remote(c: &mut Criterion) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        serve(cliff_world, reader, stream).unwrap();
+    });
+    let mut env: Remote<GridIndex, GridWorldAction> = Remote::connect(address).unwrap();
+
+    let mut group = c.benchmark_group("environment_steps");
+    group.throughput(Throughput::Elements(STEPS as u64));
+    group.sample_size(10);
+    group.bench_function("remote_cliff", |b| {
+        b.iter(|| random_steps(&mut env, &mut |env| env.reset().unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, local_environments, remote);
+criterion_main!(benches);
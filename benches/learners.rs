@@ -0,0 +1,129 @@
+//! Episodes per second of the tabular learners on the cliff world and the memory-augmented
+//! T-corridor, run with `cargo bench --bench learners`. Every iteration starts from a fresh
+//! learner, so these measure the first episode of an untrained learner and don't drift as
+//! the policy improves. The average-reward learners run for a fixed number of steps on a
+//! continuing cliff world
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use reinforcement_learning::environment::continuing::Continuing;
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::m_wrapper::MWrapper;
+use reinforcement_learning::environment::t_corridor::TCorridor;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::experience_replay::Sampling;
+use reinforcement_learning::learner::*;
+use reinforcement_learning::random;
+
+const CONTINUING_STEPS: usize = 1000; // steps per run of the average-reward learners
+
+const LEARNERS: [&str; 8] = [
+    "q_learning",
+    "sarsa",
+    "n_step_sarsa",
+    "dyna_q",
+    "experience_replay",
+    "tree_backup",
+    "q_sigma",
+    "off_policy_n_step_sarsa",
+];
+
+const CONTINUING_LEARNERS: [&str; 2] = ["differential_sarsa", "r_learning"];
+
+// built anew for every iteration
+fn learner<E: Environment + 'static>(
+    name: &str,
+    terminal: Option<E::Observation>,
+) -> Box<dyn TabularLearner<E>> {
+    let config = TabularLearnerConfig::new(0.1, 0.1, 0.9, 0.);
+
+    match name {
+        "q_learning" => Box::new(QLearning::new(config, terminal)),
+        "sarsa" => Box::new(Sarsa::new(config, terminal)),
+        "n_step_sarsa" => Box::new(NStepSarsa::new(4, config, terminal).unwrap()),
+        "dyna_q" => Box::new(DynaQ::new(config, 5, terminal)),
+        "experience_replay" => Box::new(ExperienceReplay::new(
+            QLearning::new(config, terminal),
+            1000,
+            4.,
+            Sampling::Uniform,
+        )),
+        "tree_backup" => Box::new(TreeBackup::new(4, config, 0., terminal).unwrap()),
+        "q_sigma" => {
+            Box::new(QSigma::new(4, config, 0., SigmaSchedule::Alternating, terminal).unwrap())
+        }
+        "off_policy_n_step_sarsa" => {
+            Box::new(OffPolicyNStepSarsa::new(4, config, 0., terminal).unwrap())
+        }
+        _ => unreachable!("unknown learner {}", name),
+    }
+}
+
+fn bench<E: Environment + 'static>(c: &mut Criterion, task: &str, new: impl Fn() -> E) {
+    let mut group = c.benchmark_group(format!("episodes_{}", task));
+    group.throughput(Throughput::Elements(1)); // reported as episodes per second
+    let terminal = new().get_terminal();
+
+    for name in LEARNERS {
+        random::seed(0);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || (learner(name, terminal), new()),
+                |(mut learner, mut env)| learner.episode(&mut env).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn continuing_learner<E: Environment + 'static>(name: &str) -> Box<dyn TabularLearner<E>> {
+    let mut config = TabularLearnerConfig::new(0.1, 0.1, 1., 0.);
+    config.max_steps = Some(CONTINUING_STEPS); // continuing tasks never terminate
+
+    match name {
+        "differential_sarsa" => Box::new(DifferentialSarsa::new(config, 0.01, None)),
+        "r_learning" => Box::new(RLearning::new(config, 0.01, None)),
+        _ => unreachable!("unknown learner {}", name),
+    }
+}
+
+fn continuing_cliff_world(c: &mut Criterion) {
+    let mut group = c.benchmark_group("steps_continuing_cliff");
+    group.throughput(Throughput::Elements(CONTINUING_STEPS as u64)); // steps per second
+
+    for name in CONTINUING_LEARNERS {
+        random::seed(0);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let env = Continuing::new(|| GridWorld::new(cliff(12, 4)));
+                    (continuing_learner(name), env)
+                },
+                |(mut learner, mut env)| learner.episode(&mut env).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn cliff_world(c: &mut Criterion) {
+    bench(c, "cliff", || GridWorld::new(cliff(12, 4)));
+}
+
+fn m_wrapper_t_corridor(c: &mut Criterion) {
+    bench(c, "m_wrapper_t_corridor", || {
+        MWrapper::new(TCorridor::new(10))
+    });
+}
+
+criterion_group!(
+    benches,
+    cliff_world,
+    m_wrapper_t_corridor,
+    continuing_cliff_world
+);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+//! Hash map against dense Q storage, run with `cargo bench --bench q_table`
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use reinforcement_learning::environment::gridworld::{GridWorld, GridWorldDefinition};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{QLearning, QTable, TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::random;
+
+fn open_grid(size: i32) -> GridWorldDefinition {
+    GridWorldDefinition::new((size, size), (0, 0), (size - 1, size - 1), -1.)
+}
+
+fn learner(env: &GridWorld, dense: bool) -> QLearning<GridWorld> {
+    let config = TabularLearnerConfig::new(0.5, 0.1, 1., 0.);
+    let mut learner = QLearning::new(config, env.get_terminal());
+    if dense {
+        learner.data_mut().q = QTable::dense(env.indexing());
+    }
+    learner
+}
+
+fn episodes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("q_learning_episodes");
+    group.sample_size(20);
+
+    for size in [12, 50] {
+        for dense in [false, true] {
+            let name = if dense { "dense" } else { "hash_map" };
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                random::seed(0);
+                let mut learner = learner(&GridWorld::new(open_grid(size)), dense);
+                b.iter(|| {
+                    learner
+                        .episode(&mut GridWorld::new(open_grid(size)))
+                        .unwrap()
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn max_action_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("max_action_value");
+    let mut env = GridWorld::new(cliff(12, 4));
+
+    for dense in [false, true] {
+        let name = if dense { "dense" } else { "hash_map" };
+        random::seed(0);
+        let mut learner = learner(&env, dense);
+        for _ in 0..100 {
+            learner.episode(&mut env).unwrap();
+            env = GridWorld::new(cliff(12, 4));
+        }
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut total = 0.;
+                for x in 0..12 {
+                    for y in 0..4 {
+                        total += learner.max_action_value((x, y), &env).unwrap();
+                    }
+                }
+                total
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, episodes, max_action_value);
+criterion_main!(benches);
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::environment::gridworld::GridIndex;
+use crate::environment::{Environment, Reward};
+use crate::{Error, Result};
+
+/// Binary feature vector over observations, represented by its active indices
+pub trait Features<O> {
+    /// Length of the full feature vector
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Indices of the features that are 1 for observation
+    fn active(&self, observation: O) -> Result<Vec<usize>>;
+}
+
+/// Observations with a real-valued position, e.g. grid coordinates
+pub trait Coordinates {
+    fn coordinates(self) -> Vec<f32>;
+}
+
+impl Coordinates for GridIndex {
+    fn coordinates(self) -> Vec<f32> {
+        vec![self.0 as f32, self.1 as f32]
+    }
+}
+
+/// Memory contents of a single bit become an extra dimension, for `MWrapper<GridWorld>`
+impl<C: Coordinates> Coordinates for (C, u8) {
+    fn coordinates(self) -> Vec<f32> {
+        let mut coordinates = self.0.coordinates();
+        coordinates.push(self.1 as f32);
+        coordinates
+    }
+}
+
+/// Overlapping grids (tilings) over a bounded box, each offset asymmetrically
+pub struct TileCoding {
+    tilings: usize,
+    tiles: usize,      // per dimension, in each tiling
+    low: Vec<f32>,     // lower bounds of each dimension
+    width: Vec<f32>,   // tile width in each dimension
+    per_tiling: usize, // (tiles + 1)^dimensions, offsets need an extra tile
+}
+
+impl TileCoding {
+    pub fn new(tilings: usize, tiles: usize, low: Vec<f32>, high: Vec<f32>) -> Result<TileCoding> {
+        if tilings == 0 || tiles == 0 {
+            return Err(Error::InvalidConfig(
+                "tile coding needs at least one tiling and tile".to_string(),
+            ));
+        }
+        if low.len() != high.len() || low.is_empty() {
+            return Err(Error::InvalidConfig(format!(
+                "tile coding bounds {:?} and {:?} don't match",
+                low, high
+            )));
+        }
+        if low.iter().zip(high.iter()).any(|(l, h)| l >= h) {
+            return Err(Error::InvalidConfig(format!(
+                "tile coding bounds {:?} are not below {:?}",
+                low, high
+            )));
+        }
+
+        let width = low
+            .iter()
+            .zip(high.iter())
+            .map(|(l, h)| (h - l) / tiles as f32)
+            .collect();
+        let per_tiling = (tiles + 1).pow(low.len() as u32);
+
+        Ok(TileCoding {
+            tilings,
+            tiles,
+            low,
+            width,
+            per_tiling,
+        })
+    }
+
+    /// Bounds covering a w x h grid, coordinates being cell indices
+    pub fn grid(tilings: usize, tiles: usize, (w, h): GridIndex) -> Result<TileCoding> {
+        TileCoding::new(tilings, tiles, vec![0., 0.], vec![w as f32, h as f32])
+    }
+
+    pub fn tilings(&self) -> usize {
+        self.tilings
+    }
+}
+
+impl<O: Coordinates> Features<O> for TileCoding {
+    fn len(&self) -> usize {
+        self.tilings * self.per_tiling
+    }
+
+    fn active(&self, observation: O) -> Result<Vec<usize>> {
+        let coordinates = observation.coordinates();
+        if coordinates.len() != self.low.len() {
+            return Err(Error::InvalidValue(format!(
+                "expected {} coordinates, got {:?}",
+                self.low.len(),
+                coordinates
+            )));
+        }
+
+        Ok((0..self.tilings)
+            .map(|tiling| {
+                let mut index = 0;
+                for (d, x) in coordinates.iter().enumerate().rev() {
+                    // displacement (1, 3, 5, ...) in units of width / tilings
+                    let offset =
+                        ((tiling * (2 * d + 1)) % self.tilings) as f32 / self.tilings as f32;
+                    let tile = ((x - self.low[d]) / self.width[d] + offset).floor();
+                    let tile = tile.max(0.).min(self.tiles as f32) as usize;
+                    index = index * (self.tiles + 1) + tile;
+                }
+                tiling * self.per_tiling + index
+            })
+            .collect())
+    }
+}
+
+/// One feature per observation, from an explicit list. Equivalent to a tabular representation
+pub struct OneHot<O: Hash + Eq> {
+    index: HashMap<O, usize>,
+}
+
+impl<O: Copy + Hash + Eq + std::fmt::Debug> OneHot<O> {
+    pub fn new(observations: Vec<O>) -> OneHot<O> {
+        let mut index = HashMap::new();
+        for observation in observations {
+            let next = index.len();
+            index.entry(observation).or_insert(next);
+        }
+        OneHot { index }
+    }
+}
+
+impl<O: Copy + Hash + Eq + std::fmt::Debug> Features<O> for OneHot<O> {
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn active(&self, observation: O) -> Result<Vec<usize>> {
+        match self.index.get(&observation) {
+            Some(i) => Ok(vec![*i]),
+            None => Err(Error::InvalidValue(format!(
+                "{:?} has no one-hot feature",
+                observation
+            ))),
+        }
+    }
+}
+
+/// q(s, a) = w_a . x(s), with a separate weight vector per action. Weights start at zero
+pub struct LinearQ<E: Environment, F: Features<E::Observation>> {
+    pub features: F,
+    pub weights: HashMap<E::Action, Vec<f32>>,
+}
+
+impl<E: Environment, F: Features<E::Observation>> LinearQ<E, F> {
+    pub fn new(features: F) -> LinearQ<E, F> {
+        LinearQ {
+            features,
+            weights: HashMap::new(),
+        }
+    }
+
+    fn value_of(&self, active: &[usize], action: E::Action) -> Reward {
+        match self.weights.get(&action) {
+            Some(w) => active.iter().map(|i| w[*i]).sum(),
+            None => 0.,
+        }
+    }
+
+    pub fn value(&self, state: E::Observation, action: E::Action) -> Result<Reward> {
+        Ok(self.value_of(&self.features.active(state)?, action))
+    }
+
+    /// Semi-gradient step w_a += alpha * (target - q(s, a)) * x(s), returning the new value.
+    /// With tile coding, alpha is usually divided by the number of tilings
+    pub fn update(
+        &mut self,
+        alpha: f32,
+        state: E::Observation,
+        action: E::Action,
+        target: Reward,
+    ) -> Result<Reward> {
+        let active = self.features.active(state)?;
+        let error = target - self.value_of(&active, action);
+        let len = self.features.len();
+        let w = self.weights.entry(action).or_insert_with(|| vec![0.; len]);
+        for i in active.iter() {
+            w[*i] += alpha * error;
+        }
+
+        Ok(self.value_of(&active, action))
+    }
+}
@@ -0,0 +1,93 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::env;
+use std::io::{self, BufReader};
+use std::net::TcpListener;
+use std::process;
+
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::m_wrapper::MWrapper;
+use reinforcement_learning::environment::t_corridor::TCorridor;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::protocol::serve;
+use reinforcement_learning::Result;
+
+const USAGE: &str = "usage: env_server <cliff|t_corridor|m_t_corridor> [--tcp ADDRESS] [--steps N]";
+
+/// Serves over stdin/stdout, or one connection at a time on a local socket
+fn run<E, F>(new: F, tcp: Option<String>) -> Result<()>
+where
+    E: Environment,
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+    F: Fn() -> E,
+{
+    match tcp {
+        None => serve(new, io::stdin().lock(), io::stdout().lock()),
+        Some(address) => {
+            let listener = TcpListener::bind(&address)?;
+            eprintln!("listening on {}", listener.local_addr()?);
+
+            for stream in listener.incoming() {
+                let stream = stream?;
+                stream.set_nodelay(true)?;
+                let reader = BufReader::new(stream.try_clone()?);
+                // a broken connection shouldn't take the server down
+                if let Err(e) = serve(&new, reader, stream) {
+                    eprintln!("connection failed: {}", e);
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut name = None;
+    let mut tcp = None;
+    let mut normal_steps = 6;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--tcp" if i + 1 < args.len() => {
+                tcp = Some(args[i + 1].clone());
+                i += 1;
+            }
+            "--steps" if i + 1 < args.len() => {
+                normal_steps = match args[i + 1].parse() {
+                    Ok(n) => n,
+                    Err(e) => {
+                        eprintln!("invalid --steps: {}", e);
+                        process::exit(2);
+                    }
+                };
+                i += 1;
+            }
+            arg if name.is_none() && !arg.starts_with("--") => name = Some(arg.to_string()),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        }
+        i += 1;
+    }
+
+    let result = match name.as_deref() {
+        Some("cliff") => run(|| GridWorld::new(cliff(12, 4)), tcp),
+        Some("t_corridor") => run(|| TCorridor::new(normal_steps), tcp),
+        Some("m_t_corridor") => run(|| MWrapper::new(TCorridor::new(normal_steps)), tcp),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
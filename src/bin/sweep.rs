@@ -0,0 +1,116 @@
+use std::env;
+use std::fs::File;
+use std::io;
+use std::process;
+use std::str::FromStr;
+
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::m_wrapper::MWrapper;
+use reinforcement_learning::environment::t_corridor::TCorridor;
+use reinforcement_learning::sweep::{
+    sweep, write_table, Search, SearchSpace, SweepConfig, SweepResult,
+};
+use reinforcement_learning::Result;
+
+const USAGE: &str = "usage: sweep <cliff|t_corridor|m_t_corridor> \
+<q_learning|sarsa|n_step_sarsa|dyna_q> [--alpha R] [--epsilon R] [--gamma R] [--initial-q R] \
+[--n R] [--steps N] [--episodes N] [--eval N] [--max-steps N] [--seeds N] [--random SAMPLES] [--out PATH]
+ranges R are lists like 0.1,0.2 or, for random search, intervals like 0.01..0.5";
+
+fn parse<T: FromStr>(flag: &str, value: &str) -> T
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().unwrap_or_else(|e| {
+        eprintln!("invalid {}: {}", flag, e);
+        process::exit(2);
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut positional = Vec::new();
+    let mut space = SearchSpace::new();
+    let mut normal_steps = 6;
+    let mut episodes = 500;
+    let mut eval_episodes = 10;
+    let mut max_steps = 10_000;
+    let mut seeds = 5;
+    let mut search = Search::Grid;
+    let mut out = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if !flag.starts_with("--") {
+            positional.push(flag.to_string());
+            i += 1;
+            continue;
+        }
+        let value = match args.get(i + 1) {
+            Some(value) => value.as_str(),
+            None => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        };
+        match flag {
+            "--alpha" => space.alpha = parse(flag, value),
+            "--epsilon" => space.epsilon = parse(flag, value),
+            "--gamma" => space.gamma = parse(flag, value),
+            "--initial-q" => space.initial_q = parse(flag, value),
+            "--n" => space.n = parse(flag, value),
+            "--steps" => normal_steps = parse(flag, value),
+            "--episodes" => episodes = parse(flag, value),
+            "--eval" => eval_episodes = parse(flag, value),
+            "--max-steps" => max_steps = parse(flag, value),
+            "--seeds" => seeds = parse(flag, value),
+            "--random" => {
+                search = Search::Random {
+                    samples: parse(flag, value),
+                    seed: 0,
+                }
+            }
+            "--out" => out = Some(value.to_string()),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        }
+        i += 2;
+    }
+
+    if positional.len() != 2 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    let config = SweepConfig {
+        algorithm: parse("algorithm", &positional[1]),
+        space,
+        search,
+        seeds: (0..seeds).collect(),
+        episodes,
+        eval_episodes,
+        max_steps,
+    };
+
+    let results: Result<Vec<SweepResult>> = match positional[0].as_str() {
+        "cliff" => sweep(&config, || GridWorld::new(cliff(12, 4))),
+        "t_corridor" => sweep(&config, || TCorridor::new(normal_steps)),
+        "m_t_corridor" => sweep(&config, || MWrapper::new(TCorridor::new(normal_steps))),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let written = results.and_then(|results| match &out {
+        Some(path) => write_table(&results, File::create(path)?),
+        None => write_table(&results, io::stdout().lock()),
+    });
+    if let Err(e) = written {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
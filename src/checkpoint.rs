@@ -0,0 +1,190 @@
+//! Checkpointing of long training runs. A checkpoint holds the learner's state, the episode
+//! counter and the state of the thread's random number generator, so a resumed run continues
+//! exactly like the uninterrupted one would have.
+use rand_pcg::Pcg64;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::environment::Environment;
+use crate::learner::{EpisodeStats, TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use crate::{random, Result};
+
+/// Stores f32s as their bits, as decimal JSON doesn't always restore them exactly.
+/// Use with `#[serde(with = "crate::checkpoint::exact")]`
+pub mod exact {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        value.to_bits().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        Ok(f32::from_bits(u32::deserialize(deserializer)?))
+    }
+}
+
+/// Learners whose full state can be saved and restored
+pub trait Checkpointable {
+    type State: Serialize + DeserializeOwned;
+
+    fn save(&self) -> Self::State;
+    fn restore(&mut self, state: Self::State);
+}
+
+/// State shared by all tabular learners, the Q table sorted by key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TabularState<S, A> {
+    pub config: TabularLearnerConfig,
+    pub terminal_state: Option<S>,
+    pub q: Vec<(S, A, u32)>, // value bits
+}
+
+impl<S: Copy + Ord + Hash, A: Copy + Ord + Hash> TabularState<S, A> {
+    pub fn save<E: Environment<Observation = S, Action = A>>(
+        config: &TabularLearnerConfig,
+        data: &TabularLearnerData<E>,
+    ) -> TabularState<S, A> {
+        let mut q: Vec<(S, A, u32)> = data
+            .q
+            .iter()
+            .map(|((s, a), v)| (s, a, v.to_bits()))
+            .collect();
+        q.sort_by(|(s1, a1, _), (s2, a2, _)| (s1, a1).cmp(&(s2, a2)));
+
+        TabularState {
+            config: config.clone(),
+            terminal_state: data.terminal_state,
+            q,
+        }
+    }
+
+    pub fn restore<E: Environment<Observation = S, Action = A>>(
+        self,
+        config: &mut TabularLearnerConfig,
+        data: &mut TabularLearnerData<E>,
+    ) {
+        *config = self.config;
+        data.terminal_state = self.terminal_state;
+        data.q.clear(); // keeps dense storage
+        for (s, a, bits) in self.q {
+            data.q.insert((s, a), f32::from_bits(bits));
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint<L> {
+    pub episode: usize, // completed episodes
+    pub rng: Pcg64,
+    pub learner: L,
+}
+
+/// Multiplies alpha and epsilon by the factors every `every` episodes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Decay {
+    pub alpha: f32,
+    pub epsilon: f32,
+    pub every: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrainingConfig {
+    pub episodes: usize,         // total, including those before a resume
+    pub checkpoint_every: usize, // episodes, 0 disables checkpoints
+    pub decay: Option<Decay>,
+}
+
+/// Writes a checkpoint through a temporary file, so an interruption never leaves a broken one
+pub fn save_checkpoint<L: Checkpointable>(path: &Path, episode: usize, learner: &L) -> Result<()> {
+    let checkpoint = Checkpoint {
+        episode,
+        rng: random::snapshot(),
+        learner: learner.save(),
+    };
+
+    let temporary = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        serde_json::to_writer(&mut writer, &checkpoint)?;
+        writer.flush()?;
+    }
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// Restores the learner and this thread's generator, returning the completed episodes
+pub fn load_checkpoint<L: Checkpointable>(path: &Path, learner: &mut L) -> Result<usize> {
+    let checkpoint: Checkpoint<L::State> =
+        serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    learner.restore(checkpoint.learner);
+    random::restore(checkpoint.rng);
+    Ok(checkpoint.episode)
+}
+
+fn run<E, L, F>(
+    learner: &mut L,
+    new: F,
+    config: &TrainingConfig,
+    path: &Path,
+    start: usize,
+) -> Result<Vec<EpisodeStats>>
+where
+    E: Environment,
+    L: TabularLearner<E> + Checkpointable,
+    F: Fn() -> E,
+{
+    let mut stats = Vec::with_capacity(config.episodes.saturating_sub(start));
+
+    for episode in start..config.episodes {
+        if let Some(decay) = config.decay {
+            if episode > 0 && decay.every > 0 && episode % decay.every == 0 {
+                learner.config_mut().alpha *= decay.alpha;
+                learner.config_mut().epsilon *= decay.epsilon;
+            }
+        }
+
+        stats.push(learner.episode(&mut new())?);
+
+        let completed = episode + 1;
+        if config.checkpoint_every > 0 && completed % config.checkpoint_every == 0 {
+            save_checkpoint(path, completed, learner)?;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Trains from scratch, checkpointing to path. Returns the stats of every episode
+pub fn train<E, L, F>(
+    learner: &mut L,
+    new: F,
+    config: &TrainingConfig,
+    path: &Path,
+) -> Result<Vec<EpisodeStats>>
+where
+    E: Environment,
+    L: TabularLearner<E> + Checkpointable,
+    F: Fn() -> E,
+{
+    run(learner, new, config, path, 0)
+}
+
+/// Continues a run from the checkpoint at path. Returns the stats of the remaining episodes
+pub fn resume<E, L, F>(
+    learner: &mut L,
+    new: F,
+    config: &TrainingConfig,
+    path: &Path,
+) -> Result<Vec<EpisodeStats>>
+where
+    E: Environment,
+    L: TabularLearner<E> + Checkpointable,
+    F: Fn() -> E,
+{
+    let start = load_checkpoint(path, learner)?;
+    run(learner, new, config, path, start)
+}
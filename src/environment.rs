@@ -1,33 +1,75 @@
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 
+use crate::Result;
+
 pub type Reward = f32;
 
+pub mod continuing;
 pub mod gridworld;
 pub mod gridworld_definitions;
+pub mod history;
 pub mod m_wrapper;
+pub mod memory;
+pub mod recorder;
+pub mod remote;
 pub mod t_corridor;
+pub mod t_maze;
+pub mod vectorized;
+
+/// A single step (S_t, A_t, R_{t+1}, S_{t+1}) of an episode, as observed by the agent
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transition<S, A> {
+    pub state: S,
+    pub action: A,
+    pub reward: Reward,
+    pub next_state: S,
+    pub terminal: bool, // next_state is terminal
+}
+
+pub type EnvTransition<E> = Transition<<E as Environment>::Observation, <E as Environment>::Action>;
 
 // Reward process interface
 pub trait Environment {
     type Action: Copy + Hash + Eq + std::fmt::Debug + PartialOrd + Ord;
-    // either an index or a state representation
-    type State: Copy + Hash + Eq + std::fmt::Debug + PartialOrd + Ord;
+    /// What the agent perceives; learners only ever see observations.
+    /// Either an index or a state representation
+    type Observation: Copy + Hash + Eq + std::fmt::Debug + PartialOrd + Ord;
+    /// Full underlying state, for evaluation tools. Same as Observation for fully observable tasks
+    type State: Copy + Hash + Eq + std::fmt::Debug;
 
-    /// Returns None on all transitions from terminal state
-    fn take_action(&mut self, action: Self::Action) -> Option<(Self::State, Reward)>;
+    /// Fails with `Error::EpisodeTerminated` on all transitions from terminal state
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)>;
 
-    /// Returns all actions available at state
-    fn available_actions(&self, state: Self::State) -> Vec<Self::Action>;
+    /// Returns all actions available when observing observation
+    fn available_actions(&self, observation: Self::Observation) -> Vec<Self::Action>;
 
+    fn current_observation(&self) -> Self::Observation;
+
+    /// True state of the process; learners should not depend on this
     fn current_state(&self) -> Self::State;
 
     /// Returns true if MDP is episodic and has reached the goal/terminal state
     fn terminated(&self) -> bool;
 
-    /// Check if state is terminal
-    fn is_terminal(&self, state: Self::State) -> bool;
+    /// Check if observation is terminal
+    fn is_terminal(&self, observation: Self::Observation) -> bool;
+
+    /// The terminal observation, None for continuing tasks that never terminate
+    fn get_terminal(&self) -> Option<Self::Observation>;
+}
 
-    fn get_terminal(&self) -> Self::State;
+/// Numbers an environment's observations and actions from 0, so values can be kept in arrays
+/// (see `QTable::dense`). Every observation and action the environment produces must be covered
+pub trait Indexing<S, A>: Send + Sync {
+    fn states(&self) -> usize;
+    fn actions(&self) -> usize;
+    fn state_index(&self, state: S) -> usize;
+    fn action_index(&self, action: A) -> usize;
+    /// Inverse of `state_index`
+    fn state(&self, index: usize) -> S;
+    /// Inverse of `action_index`
+    fn action(&self, index: usize) -> A;
 }
 
 /// Interface for a Markov decision process
@@ -0,0 +1,70 @@
+use super::{Environment, Reward};
+use crate::Result;
+
+/// Turns an episodic task into a continuing one: whenever the inner environment terminates,
+/// a fresh instance takes its place and the agent observes its start instead of the terminal
+pub struct Continuing<E: Environment, F: Fn() -> E> {
+    env: E,
+    new: F,        // creates the next episode
+    resets: usize, // completed inner episodes
+}
+
+impl<E: Environment, F: Fn() -> E> Continuing<E, F> {
+    pub fn new(new: F) -> Continuing<E, F> {
+        Continuing {
+            env: new(),
+            new,
+            resets: 0,
+        }
+    }
+
+    pub fn resets(&self) -> usize {
+        self.resets
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+}
+
+impl<E: Environment, F: Fn() -> E> Environment for Continuing<E, F> {
+    type Action = E::Action;
+    type Observation = E::Observation;
+    type State = E::State;
+
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        let (next_state, reward) = self.env.take_action(action)?;
+
+        if self.env.terminated() {
+            self.env = (self.new)();
+            self.resets += 1;
+            return Ok((self.env.current_observation(), reward));
+        }
+
+        Ok((next_state, reward))
+    }
+
+    fn available_actions(&self, state: Self::Observation) -> Vec<Self::Action> {
+        self.env.available_actions(state)
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        self.env.current_observation()
+    }
+
+    fn current_state(&self) -> Self::State {
+        self.env.current_state()
+    }
+
+    fn terminated(&self) -> bool {
+        false
+    }
+
+    fn is_terminal(&self, _state: Self::Observation) -> bool {
+        false
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        None
+    }
+}
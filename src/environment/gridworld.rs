@@ -1,8 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::cmp;
 use std::collections::HashMap;
 
 use crate::environment;
-use environment::Environment;
+use crate::{Error, Result};
+use environment::{Environment, Indexing};
 
 pub type GridIndex = (i32, i32);
 
@@ -36,7 +38,7 @@ fn apply_displacement((dx, dy): GridIndex, (w, h): GridIndex, (x, y): GridIndex)
     (x, y)
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum GridWorldAction {
     Left,
     Right,
@@ -44,6 +46,13 @@ pub enum GridWorldAction {
     Down,
 }
 
+const ACTIONS: [GridWorldAction; 4] = [
+    GridWorldAction::Left,
+    GridWorldAction::Right,
+    GridWorldAction::Up,
+    GridWorldAction::Down,
+];
+
 impl GridWorldAction {
     pub fn displacement(self) -> GridIndex {
         match self {
@@ -118,17 +127,60 @@ impl GridWorld {
             definition,
         }
     }
+
+    pub fn indexing(&self) -> GridIndexing {
+        GridIndexing {
+            dimensions: self.definition.dimensions,
+        }
+    }
+}
+
+/// Cells numbered row by row
+#[derive(Clone, Copy, Debug)]
+pub struct GridIndexing {
+    dimensions: GridIndex,
+}
+
+impl Indexing<GridIndex, GridWorldAction> for GridIndexing {
+    fn states(&self) -> usize {
+        (self.dimensions.0 * self.dimensions.1) as usize
+    }
+
+    fn actions(&self) -> usize {
+        ACTIONS.len()
+    }
+
+    fn state_index(&self, (x, y): GridIndex) -> usize {
+        (y * self.dimensions.0 + x) as usize
+    }
+
+    fn action_index(&self, action: GridWorldAction) -> usize {
+        action as usize
+    }
+
+    fn state(&self, index: usize) -> GridIndex {
+        let index = index as i32;
+        (index % self.dimensions.0, index / self.dimensions.0)
+    }
+
+    fn action(&self, index: usize) -> GridWorldAction {
+        ACTIONS[index]
+    }
 }
 
 impl environment::MDP for GridWorld {}
 
 impl Environment for GridWorld {
     type Action = GridWorldAction;
+    type Observation = GridIndex;
     type State = GridIndex;
 
-    fn take_action(&mut self, action: Self::Action) -> Option<(Self::State, environment::Reward)> {
+    fn take_action(
+        &mut self,
+        action: Self::Action,
+    ) -> Result<(Self::Observation, environment::Reward)> {
         if self.current_state == self.definition.end_state {
-            return None;
+            return Err(Error::EpisodeTerminated);
         }
 
         let next_state = apply_displacement(
@@ -161,16 +213,15 @@ impl Environment for GridWorld {
 
         self.current_state = effect_state;
 
-        Some((effect_state, reward))
+        Ok((effect_state, reward))
     }
 
-    fn available_actions(&self, _: Self::State) -> Vec<Self::Action> {
-        vec![
-            GridWorldAction::Left,
-            GridWorldAction::Right,
-            GridWorldAction::Up,
-            GridWorldAction::Down,
-        ]
+    fn available_actions(&self, _: Self::Observation) -> Vec<Self::Action> {
+        ACTIONS.to_vec()
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        self.current_state
     }
 
     fn current_state(&self) -> Self::State {
@@ -181,11 +232,11 @@ impl Environment for GridWorld {
         self.current_state == self.definition.end_state
     }
 
-    fn is_terminal(&self, state: Self::State) -> bool {
+    fn is_terminal(&self, state: Self::Observation) -> bool {
         state == self.definition.end_state
     }
 
-    fn get_terminal(&self) -> Self::State {
-        self.definition.end_state
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        Some(self.definition.end_state)
     }
 }
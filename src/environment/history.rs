@@ -0,0 +1,101 @@
+use super::{Environment, Reward};
+use crate::Result;
+
+/// Which parts of the past are added to the observation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryMode {
+    Observations,
+    Actions,
+    Both,
+}
+
+/// The last K observations and/or actions, most recent first
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Window<O, A, const K: usize> {
+    pub observations: [Option<O>; K],
+    pub actions: [Option<A>; K],
+}
+
+impl<O: Copy, A: Copy, const K: usize> Window<O, A, K> {
+    fn empty() -> Window<O, A, K> {
+        Window {
+            observations: [None; K],
+            actions: [None; K],
+        }
+    }
+}
+
+fn push_front<T: Copy, const K: usize>(items: &mut [Option<T>; K], item: T) {
+    if K > 0 {
+        items.rotate_right(1);
+        items[0] = Some(item);
+    }
+}
+
+/// Fixed-window memory baseline: augments observations with the last K observations and/or
+/// actions. The window is cleared on termination so that the terminal observation is unique.
+pub struct HistoryWrapper<E: Environment, const K: usize> {
+    env: E, // environment to wrap
+    mode: HistoryMode,
+    window: Window<E::Observation, E::Action, K>,
+}
+
+impl<E: Environment, const K: usize> HistoryWrapper<E, K> {
+    pub fn new(env: E, mode: HistoryMode) -> HistoryWrapper<E, K> {
+        HistoryWrapper {
+            env,
+            mode,
+            window: Window::empty(),
+        }
+    }
+}
+
+impl<E: Environment, const K: usize> Environment for HistoryWrapper<E, K> {
+    type Action = E::Action;
+    type Observation = (E::Observation, Window<E::Observation, E::Action, K>);
+    type State = (E::State, Window<E::Observation, E::Action, K>);
+
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        let observation = self.env.current_observation();
+        let (next_env_observation, reward) = self.env.take_action(action)?;
+
+        if self.env.terminated() {
+            self.window = Window::empty();
+        } else {
+            if self.mode != HistoryMode::Actions {
+                push_front(&mut self.window.observations, observation);
+            }
+            if self.mode != HistoryMode::Observations {
+                push_front(&mut self.window.actions, action);
+            }
+        }
+
+        Ok(((next_env_observation, self.window), reward))
+    }
+
+    fn available_actions(&self, (env_observation, _): Self::Observation) -> Vec<Self::Action> {
+        self.env.available_actions(env_observation)
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        (self.env.current_observation(), self.window)
+    }
+
+    fn current_state(&self) -> Self::State {
+        (self.env.current_state(), self.window)
+    }
+
+    fn terminated(&self) -> bool {
+        self.env.terminated()
+    }
+
+    fn is_terminal(&self, (env_observation, _): Self::Observation) -> bool {
+        self.env.is_terminal(env_observation)
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        self.env
+            .get_terminal()
+            .map(|terminal| (terminal, Window::empty()))
+    }
+}
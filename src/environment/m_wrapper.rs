@@ -1,89 +1,140 @@
+use super::memory::{Bit, Memory};
 use super::{Environment, Reward};
+use crate::{Error, Result};
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord)]
-pub enum MAction {
-    Flip,
-    Noop,
+pub use super::memory::MAction;
+
+#[derive(Clone, Debug)]
+pub struct MWrapperConfig {
+    pub write_cost: Reward, // subtracted from the reward whenever memory is written
+    pub internal_actions: bool, // allow memory-only steps that don't advance the environment
+    pub internal_reward: Reward, // reward for a memory-only step, before the write cost
 }
 
-impl MAction {
-    fn bit_value(self, m: u8) -> u8 {
-        match self {
-            MAction::Flip => {
-                if m == 1 {
-                    0
-                } else {
-                    1
-                }
-            }
-            MAction::Noop => m,
+impl MWrapperConfig {
+    pub fn new() -> MWrapperConfig {
+        MWrapperConfig {
+            write_cost: 0.,
+            internal_actions: false,
+            internal_reward: 0.,
         }
     }
 }
 
-pub struct MWrapper<E: Environment> {
+impl Default for MWrapperConfig {
+    fn default() -> MWrapperConfig {
+        MWrapperConfig::new()
+    }
+}
+
+/// Gives the agent access to an external memory, a single bit by default
+pub struct MWrapper<E: Environment, M: Memory = Bit> {
     env: E, // environment to wrap
-    m: u8,  // memory bits
+    config: MWrapperConfig,
+    memory: M,
 }
 
-impl<E: Environment> MWrapper<E> {
-    pub fn new(env: E) -> MWrapper<E> {
-        MWrapper { env, m: 0 }
+impl<E: Environment> MWrapper<E, Bit> {
+    pub fn new(env: E) -> MWrapper<E, Bit> {
+        MWrapper::with_config(env, MWrapperConfig::new())
+    }
+
+    pub fn with_config(env: E, config: MWrapperConfig) -> MWrapper<E, Bit> {
+        MWrapper::with_memory(env, Bit::new(), config)
+    }
+}
+
+impl<E: Environment, M: Memory> MWrapper<E, M> {
+    pub fn with_memory(env: E, memory: M, config: MWrapperConfig) -> MWrapper<E, M> {
+        MWrapper {
+            env,
+            config,
+            memory,
+        }
+    }
+
+    // terminal contents keep the terminal observation the same in every episode
+    fn contents(&self) -> M::Contents {
+        if self.env.terminated() {
+            self.memory.terminal_contents()
+        } else {
+            self.memory.contents()
+        }
     }
 }
 
-impl<E: Environment> Environment for MWrapper<E> {
-    type Action = (E::Action, MAction);
-    type State = (E::State, u8);
+impl<E: Environment, M: Memory> Environment for MWrapper<E, M> {
+    /// A None environment action is a memory-only step (see `MWrapperConfig::internal_actions`)
+    type Action = (Option<E::Action>, M::Action);
+    type Observation = (E::Observation, M::Contents);
+    type State = (E::State, M::Contents);
 
     fn take_action(
         &mut self,
         (env_action, m_action): Self::Action,
-    ) -> Option<(Self::State, Reward)> {
-        match self.env.take_action(env_action) {
-            None => None,
-            Some((next_env_state, reward)) => {
-                self.m = m_action.bit_value(self.m);
-                if self.env.terminated() {
-                    self.m = 2;
-                }
-                Some(((next_env_state, self.m), reward))
+    ) -> Result<(Self::Observation, Reward)> {
+        let (next_env_state, reward) = match env_action {
+            Some(env_action) => self.env.take_action(env_action)?,
+            None if !self.config.internal_actions => {
+                return Err(Error::InvalidConfig(
+                    "memory-only step with internal actions disabled".to_string(),
+                ))
             }
-        }
+            None if self.env.terminated() => return Err(Error::EpisodeTerminated),
+            None => (self.env.current_observation(), self.config.internal_reward),
+        };
+
+        let written = self.memory.apply(m_action);
+        let cost = if written { self.config.write_cost } else { 0. };
+
+        Ok(((next_env_state, self.contents()), reward - cost))
     }
 
-    fn available_actions(&self, (env_state, _m_state): Self::State) -> Vec<Self::Action> {
-        self.env
+    fn available_actions(&self, (env_state, contents): Self::Observation) -> Vec<Self::Action> {
+        let m_actions = self.memory.actions(contents);
+
+        let mut actions: Vec<Self::Action> = self
+            .env
             .available_actions(env_state)
             .iter()
-            .map(|env_action| vec![(*env_action, MAction::Flip), (*env_action, MAction::Noop)])
-            .flatten()
-            .collect()
+            .flat_map(|env_action| {
+                m_actions
+                    .iter()
+                    .map(move |m_action| (Some(*env_action), *m_action))
+            })
+            .collect();
+
+        if self.config.internal_actions {
+            actions.extend(
+                m_actions
+                    .iter()
+                    .filter(|m_action| !self.memory.is_noop(**m_action))
+                    .map(|m_action| (None, *m_action)),
+            );
+        }
+
+        actions
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        (self.env.current_observation(), self.contents())
     }
 
     fn current_state(&self) -> Self::State {
-        (self.env.current_state(), self.m)
+        (self.env.current_state(), self.contents())
     }
 
     fn terminated(&self) -> bool {
-        if self.env.terminated() || self.m == 2 {
-            assert!(self.env.terminated() && self.m == 2);
-            return true;
-        } else {
-            return false;
-        }
+        self.env.terminated()
     }
 
-    fn is_terminal(&self, (env_state, m_state): Self::State) -> bool {
-        if self.env.is_terminal(env_state) || m_state == 2 {
-            assert!(self.env.is_terminal(env_state) && m_state == 2);
-            return true;
-        } else {
-            return false;
-        }
+    fn is_terminal(&self, (env_state, _contents): Self::Observation) -> bool {
+        self.env.is_terminal(env_state)
     }
 
-    fn get_terminal(&self) -> Self::State {
-        (self.env.get_terminal(), 2)
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        self.env
+            .get_terminal()
+            .map(|terminal| (terminal, self.memory.terminal_contents()))
     }
 }
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// External memory that an agent can read (through its contents) and write (through actions)
+pub trait Memory {
+    type Action: Copy + Hash + Eq + Debug + PartialOrd + Ord;
+    /// Observable contents, added to the observation
+    type Contents: Copy + Hash + Eq + Debug + PartialOrd + Ord;
+
+    /// Memory actions available given contents
+    fn actions(&self, contents: Self::Contents) -> Vec<Self::Action>;
+
+    /// Applies action, returning true if memory was written
+    fn apply(&mut self, action: Self::Action) -> bool;
+
+    fn contents(&self) -> Self::Contents;
+
+    /// True for actions that never write; these are skipped on memory-only steps
+    fn is_noop(&self, action: Self::Action) -> bool;
+
+    /// Contents once the wrapped environment terminates, must be the same in every episode
+    fn terminal_contents(&self) -> Self::Contents;
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MAction {
+    Flip,
+    Noop,
+}
+
+impl MAction {
+    fn bit_value(self, m: u8) -> u8 {
+        match self {
+            MAction::Flip => {
+                if m == 1 {
+                    0
+                } else {
+                    1
+                }
+            }
+            MAction::Noop => m,
+        }
+    }
+}
+
+/// A single bit which can be flipped, 2 marks termination
+pub struct Bit {
+    m: u8,
+}
+
+impl Bit {
+    pub fn new() -> Bit {
+        Bit { m: 0 }
+    }
+}
+
+impl Default for Bit {
+    fn default() -> Bit {
+        Bit::new()
+    }
+}
+
+impl Memory for Bit {
+    type Action = MAction;
+    type Contents = u8;
+
+    fn actions(&self, _: u8) -> Vec<MAction> {
+        vec![MAction::Flip, MAction::Noop]
+    }
+
+    fn apply(&mut self, action: MAction) -> bool {
+        self.m = action.bit_value(self.m);
+        action == MAction::Flip
+    }
+
+    fn contents(&self) -> u8 {
+        self.m
+    }
+
+    fn is_noop(&self, action: MAction) -> bool {
+        action == MAction::Noop
+    }
+
+    fn terminal_contents(&self) -> u8 {
+        2
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RegisterAction {
+    Set(u8),
+    Noop,
+}
+
+/// Write-once register holding one of `values` symbols (one-hot), empty until set
+pub struct Register {
+    values: u8,
+    value: Option<u8>,
+}
+
+impl Register {
+    pub fn new(values: u8) -> Register {
+        Register {
+            values,
+            value: None,
+        }
+    }
+}
+
+impl Memory for Register {
+    type Action = RegisterAction;
+    type Contents = Option<u8>;
+
+    fn actions(&self, contents: Option<u8>) -> Vec<RegisterAction> {
+        let mut actions = vec![RegisterAction::Noop];
+        if contents.is_none() {
+            actions.extend((0..self.values).map(RegisterAction::Set));
+        }
+        actions
+    }
+
+    fn apply(&mut self, action: RegisterAction) -> bool {
+        match action {
+            RegisterAction::Set(value) if self.value.is_none() => {
+                self.value = Some(value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn contents(&self) -> Option<u8> {
+        self.value
+    }
+
+    fn is_noop(&self, action: RegisterAction) -> bool {
+        action == RegisterAction::Noop
+    }
+
+    fn terminal_contents(&self) -> Option<u8> {
+        None
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StackAction {
+    Push(u8),
+    Pop,
+    Noop,
+}
+
+/// Stack of at most D symbols out of `symbols`, contents listed bottom first
+pub struct Stack<const D: usize> {
+    symbols: u8,
+    items: [Option<u8>; D],
+}
+
+impl<const D: usize> Stack<D> {
+    pub fn new(symbols: u8) -> Stack<D> {
+        Stack {
+            symbols,
+            items: [None; D],
+        }
+    }
+}
+
+fn occupied(items: &[Option<u8>]) -> usize {
+    items.iter().take_while(|item| item.is_some()).count()
+}
+
+impl<const D: usize> Memory for Stack<D> {
+    type Action = StackAction;
+    type Contents = [Option<u8>; D];
+
+    fn actions(&self, contents: [Option<u8>; D]) -> Vec<StackAction> {
+        let len = occupied(&contents);
+        let mut actions = vec![StackAction::Noop];
+        if len > 0 {
+            actions.push(StackAction::Pop);
+        }
+        if len < D {
+            actions.extend((0..self.symbols).map(StackAction::Push));
+        }
+        actions
+    }
+
+    fn apply(&mut self, action: StackAction) -> bool {
+        let len = occupied(&self.items);
+        match action {
+            StackAction::Push(symbol) if len < D => {
+                self.items[len] = Some(symbol);
+                true
+            }
+            StackAction::Pop if len > 0 => {
+                self.items[len - 1] = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn contents(&self) -> [Option<u8>; D] {
+        self.items
+    }
+
+    fn is_noop(&self, action: StackAction) -> bool {
+        action == StackAction::Noop
+    }
+
+    fn terminal_contents(&self) -> [Option<u8>; D] {
+        [None; D]
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum QueueAction {
+    Enqueue(u8),
+    Dequeue,
+    Noop,
+}
+
+/// FIFO queue of at most D symbols out of `symbols`, contents listed front first
+pub struct Queue<const D: usize> {
+    symbols: u8,
+    items: [Option<u8>; D],
+}
+
+impl<const D: usize> Queue<D> {
+    pub fn new(symbols: u8) -> Queue<D> {
+        Queue {
+            symbols,
+            items: [None; D],
+        }
+    }
+}
+
+impl<const D: usize> Memory for Queue<D> {
+    type Action = QueueAction;
+    type Contents = [Option<u8>; D];
+
+    fn actions(&self, contents: [Option<u8>; D]) -> Vec<QueueAction> {
+        let len = occupied(&contents);
+        let mut actions = vec![QueueAction::Noop];
+        if len > 0 {
+            actions.push(QueueAction::Dequeue);
+        }
+        if len < D {
+            actions.extend((0..self.symbols).map(QueueAction::Enqueue));
+        }
+        actions
+    }
+
+    fn apply(&mut self, action: QueueAction) -> bool {
+        let len = occupied(&self.items);
+        match action {
+            QueueAction::Enqueue(symbol) if len < D => {
+                self.items[len] = Some(symbol);
+                true
+            }
+            QueueAction::Dequeue if len > 0 => {
+                self.items.rotate_left(1);
+                self.items[D - 1] = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn contents(&self) -> [Option<u8>; D] {
+        self.items
+    }
+
+    fn is_noop(&self, action: QueueAction) -> bool {
+        action == QueueAction::Noop
+    }
+
+    fn terminal_contents(&self) -> [Option<u8>; D] {
+        [None; D]
+    }
+}
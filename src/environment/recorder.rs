@@ -0,0 +1,133 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use super::{EnvTransition, Environment, Reward, Transition};
+use crate::Result;
+
+pub type Trajectory<S, A> = Vec<Transition<S, A>>;
+
+/// Wraps an environment and records every transition taken in it, along with the true states
+/// the agent did not see
+pub struct Recorder<E: Environment> {
+    env: E, // environment to record
+    trajectory: Vec<EnvTransition<E>>,
+    states: Vec<E::State>, // true state after each transition
+}
+
+impl<E: Environment> Recorder<E> {
+    pub fn new(env: E) -> Recorder<E> {
+        Recorder {
+            env,
+            trajectory: Vec::new(),
+            states: Vec::new(),
+        }
+    }
+
+    pub fn trajectory(&self) -> &[EnvTransition<E>] {
+        &self.trajectory
+    }
+
+    pub fn states(&self) -> &[E::State] {
+        &self.states
+    }
+
+    pub fn into_trajectory(self) -> Vec<EnvTransition<E>> {
+        self.trajectory
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+}
+
+impl<E: Environment> Environment for Recorder<E> {
+    type Action = E::Action;
+    type Observation = E::Observation;
+    type State = E::State;
+
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        let state = self.env.current_observation();
+        let (next_state, reward) = self.env.take_action(action)?;
+        self.trajectory.push(Transition {
+            state,
+            action,
+            reward,
+            next_state,
+            terminal: self.env.is_terminal(next_state),
+        });
+        self.states.push(self.env.current_state());
+        Ok((next_state, reward))
+    }
+
+    fn available_actions(&self, state: Self::Observation) -> Vec<Self::Action> {
+        self.env.available_actions(state)
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        self.env.current_observation()
+    }
+
+    fn current_state(&self) -> Self::State {
+        self.env.current_state()
+    }
+
+    fn terminated(&self) -> bool {
+        self.env.terminated()
+    }
+
+    fn is_terminal(&self, state: Self::Observation) -> bool {
+        self.env.is_terminal(state)
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        self.env.get_terminal()
+    }
+}
+
+/// Writes a trajectory as a single line of JSON
+pub fn write_trajectory<S: Serialize, A: Serialize, W: Write>(
+    writer: &mut W,
+    trajectory: &[Transition<S, A>],
+) -> Result<()> {
+    serde_json::to_writer(&mut *writer, trajectory)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Reads trajectories written by `write_trajectory`, one per line
+pub fn read_trajectories<S: DeserializeOwned, A: DeserializeOwned, R: BufRead>(
+    reader: R,
+) -> Result<Vec<Trajectory<S, A>>> {
+    let mut trajectories = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        trajectories.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(trajectories)
+}
+
+/// Appends a trajectory to the file at path, creating it if needed
+pub fn append_trajectory<S: Serialize, A: Serialize, P: AsRef<Path>>(
+    path: P,
+    trajectory: &[Transition<S, A>],
+) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    write_trajectory(&mut writer, trajectory)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn load_trajectories<S: DeserializeOwned, A: DeserializeOwned, P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<Trajectory<S, A>>> {
+    read_trajectories(BufReader::new(File::open(path)?))
+}
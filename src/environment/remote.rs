@@ -0,0 +1,249 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Child, Command, Stdio};
+
+use super::{Environment, Reward};
+use crate::protocol::{Request, Response};
+use crate::{Error, Result};
+
+/// Observation and action types that can cross the protocol
+pub trait RemoteValue:
+    Copy + Hash + Eq + Debug + PartialOrd + Ord + Serialize + DeserializeOwned
+{
+}
+impl<T: Copy + Hash + Eq + Debug + PartialOrd + Ord + Serialize + DeserializeOwned> RemoteValue
+    for T
+{
+}
+
+struct Connection {
+    reader: Box<dyn BufRead>,
+    writer: Box<dyn Write>,
+}
+
+impl Connection {
+    fn request<O: RemoteValue, A: RemoteValue>(
+        &mut self,
+        request: &Request<O, A>,
+    ) -> Result<Response<O, A>> {
+        serde_json::to_writer(&mut self.writer, request)?;
+        writeln!(self.writer)?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::Remote("connection closed".to_string()));
+        }
+
+        match serde_json::from_str(&line)? {
+            Response::Error { message } => Err(Error::Remote(message)),
+            response => Ok(response),
+        }
+    }
+
+    fn reset<O: RemoteValue, A: RemoteValue>(&mut self) -> Result<(O, bool)> {
+        match self.request::<O, A>(&Request::Reset)? {
+            Response::Reset {
+                observation,
+                terminal,
+            } => Ok((observation, terminal)),
+            response => Err(unexpected(&response)),
+        }
+    }
+}
+
+/// Environment living in another process, driven through `crate::protocol`.
+/// The remote side only exposes observations, so they double as the state
+pub struct Remote<O: RemoteValue, A: RemoteValue> {
+    // available_actions takes &self but has to talk to the server
+    connection: RefCell<Connection>,
+    observation: O,
+    terminated: bool,
+    terminal: Option<O>,
+    actions: RefCell<HashMap<O, Vec<A>>>, // available actions only depend on the observation
+    error: RefCell<Option<Error>>,        // last failure of a call that can't return one
+    child: Option<Child>,
+}
+
+impl<O: RemoteValue, A: RemoteValue> Remote<O, A> {
+    /// Starts a new episode on a server reachable through reader/writer
+    pub fn new<R: BufRead + 'static, W: Write + 'static>(
+        reader: R,
+        writer: W,
+    ) -> Result<Remote<O, A>> {
+        let mut connection = Connection {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+        };
+        let terminal = match connection.request::<O, A>(&Request::Terminal)? {
+            Response::Terminal { observation } => observation,
+            response => return Err(unexpected(&response)),
+        };
+        let (observation, terminated) = connection.reset::<O, A>()?;
+
+        let remote = Remote {
+            connection: RefCell::new(connection),
+            observation,
+            terminated,
+            terminal,
+            actions: RefCell::new(HashMap::new()),
+            error: RefCell::new(None),
+            child: None,
+        };
+        remote.prefetch_actions()?;
+        Ok(remote)
+    }
+
+    /// Connects to a server listening on a local socket
+    pub fn connect<T: ToSocketAddrs>(address: T) -> Result<Remote<O, A>> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?; // one small request per step
+        Remote::new(BufReader::new(stream.try_clone()?), stream)
+    }
+
+    /// Runs a server as a child process talking over its stdin/stdout
+    pub fn spawn(command: &mut Command) -> Result<Remote<O, A>> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| no_pipe("stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| no_pipe("stdout"))?;
+
+        let mut remote = Remote::new(BufReader::new(stdout), stdin)?;
+        remote.child = Some(child);
+        Ok(remote)
+    }
+
+    /// Starts a new episode in place
+    pub fn reset(&mut self) -> Result<()> {
+        let (observation, terminated) = self.connection.get_mut().reset::<O, A>()?;
+        self.observation = observation;
+        self.terminated = terminated;
+        self.prefetch_actions()
+    }
+
+    /// Error swallowed by `available_actions`, which returns no actions instead.
+    /// The next `take_action` fails with it if it hasn't been taken by then
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.borrow_mut().take()
+    }
+
+    fn request(&self, request: &Request<O, A>) -> Result<Response<O, A>> {
+        self.connection.borrow_mut().request(request)
+    }
+
+    fn fetch_actions(&self, observation: O) -> Result<Vec<A>> {
+        match self.request(&Request::Actions {
+            observation: Some(observation),
+        })? {
+            Response::Actions { actions } => Ok(actions),
+            response => Err(unexpected(&response)),
+        }
+    }
+
+    // caches the actions of the current observation, so failures to fetch the ones learners
+    // ask for surface here rather than in `available_actions`
+    fn prefetch_actions(&self) -> Result<()> {
+        if self.terminated || self.actions.borrow().contains_key(&self.observation) {
+            return Ok(());
+        }
+
+        let actions = self.fetch_actions(self.observation)?;
+        self.actions.borrow_mut().insert(self.observation, actions);
+        Ok(())
+    }
+}
+
+fn unexpected<R: Debug>(response: &R) -> Error {
+    Error::Remote(format!("unexpected response {:?}", response))
+}
+
+fn no_pipe(name: &str) -> Error {
+    Error::Remote(format!("child process has no {}", name))
+}
+
+impl<O: RemoteValue, A: RemoteValue> Drop for Remote<O, A> {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl<O: RemoteValue, A: RemoteValue> Environment for Remote<O, A> {
+    type Action = A;
+    type Observation = O;
+    type State = O;
+
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        if let Some(e) = self.take_error() {
+            return Err(e);
+        }
+        if self.terminated {
+            return Err(Error::EpisodeTerminated);
+        }
+
+        match self.request(&Request::Step { action })? {
+            Response::Step {
+                observation,
+                reward,
+                terminal,
+            } => {
+                self.observation = observation;
+                self.terminated = terminal;
+                self.prefetch_actions()?;
+                Ok((observation, reward))
+            }
+            response => Err(unexpected(&response)),
+        }
+    }
+
+    /// Served from the cache for every observation reached so far. Fetching the actions of
+    /// any other observation may fail, in which case they're empty, see `take_error`
+    fn available_actions(&self, observation: Self::Observation) -> Vec<Self::Action> {
+        if let Some(actions) = self.actions.borrow().get(&observation) {
+            return actions.clone();
+        }
+
+        match self.fetch_actions(observation) {
+            Ok(actions) => {
+                self.actions
+                    .borrow_mut()
+                    .insert(observation, actions.clone());
+                actions
+            }
+            Err(e) => {
+                *self.error.borrow_mut() = Some(e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        self.observation
+    }
+
+    fn current_state(&self) -> Self::State {
+        self.observation
+    }
+
+    fn terminated(&self) -> bool {
+        self.terminated
+    }
+
+    fn is_terminal(&self, observation: Self::Observation) -> bool {
+        Some(observation) == self.terminal
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        self.terminal
+    }
+}
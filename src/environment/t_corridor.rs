@@ -1,9 +1,11 @@
-use super::{Environment, Reward};
+use super::{Environment, Indexing, Reward};
+use crate::{random, Error, Result};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 // State representation exposed to the agent
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum TCorridorState {
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TCorridorObservation {
     Start,
     ObserveU,        // Marks upper state as trapped
     ObserveL,        // Marks lower state as trapped
@@ -12,7 +14,29 @@ pub enum TCorridorState {
     Terminal,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TCorridorPosition {
+    Start,
+    Cue,             // trap location is observable
+    Corridor(usize), // regular part of the corridor
+    Split,
+    Terminal,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Trap {
+    Upper,
+    Lower,
+}
+
+/// Full state, including the trap location which the agent only sees at the cue
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TCorridorState {
+    pub position: TCorridorPosition,
+    pub trap: Trap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TCorridorAction {
     Forward,
     Backward,
@@ -20,42 +44,89 @@ pub enum TCorridorAction {
     Down,
 }
 
+#[derive(Clone, Debug)]
+pub struct TCorridorConfig {
+    pub normal_steps: usize,
+    pub default_reward: Reward,
+    pub trap_reward: Reward,
+    pub nontrap_reward: Reward,
+    pub upper_trap_probability: f32, // cue distribution
+    pub backward: bool,              // allow Backward at the cue, corridor and split
+    pub sideways: bool,              // allow Up/Down (no-ops) before the split
+    pub random_length: bool,         // draw the length uniformly from 0..=normal_steps
+}
+
+impl TCorridorConfig {
+    pub fn new(normal_steps: usize) -> TCorridorConfig {
+        TCorridorConfig {
+            normal_steps,
+            default_reward: -5.0,
+            trap_reward: -100.0,
+            nontrap_reward: 100.0,
+            upper_trap_probability: 0.5,
+            backward: false,
+            sideways: false,
+            random_length: false,
+        }
+    }
+}
+
 pub struct TCorridor {
-    current_state: TCorridorState,
-    observed: i8, // -1 is lower, 1 is upper
-    normal_steps: usize,
+    config: TCorridorConfig,
+    state: TCorridorState,
+    normal_steps: usize, // length of this episode's corridor
 }
 
 impl TCorridor {
     pub fn new(normal_steps: usize) -> TCorridor {
+        TCorridor::with_config(TCorridorConfig::new(normal_steps))
+    }
+
+    pub fn with_config(config: TCorridorConfig) -> TCorridor {
+        let mut rng = random::rng();
+        let trap = if rng.gen::<f32>() < config.upper_trap_probability {
+            Trap::Upper
+        } else {
+            Trap::Lower
+        };
+        let normal_steps = if config.random_length {
+            rng.gen_range(0, config.normal_steps + 1)
+        } else {
+            config.normal_steps
+        };
+
         TCorridor {
-            current_state: TCorridorState::Start,
-            observed: 0,
+            config,
+            state: TCorridorState {
+                position: TCorridorPosition::Start,
+                trap,
+            },
             normal_steps,
         }
     }
 
-    fn observe(&mut self) -> TCorridorState {
-        if self.observed == 1 {
-            return TCorridorState::ObserveU;
-        }
-
-        if self.observed == -1 {
-            return TCorridorState::ObserveL;
+    /// Covers corridors of up to the configured length, so every episode of this config
+    pub fn indexing(&self) -> TCorridorIndexing {
+        TCorridorIndexing {
+            max_steps: self.config.normal_steps,
         }
+    }
 
-        let mut rng = rand::thread_rng();
-        if rng.gen::<f32>() < 0.5 {
-            self.observed = 1;
-            TCorridorState::ObserveU
-        } else {
-            self.observed = -1;
-            TCorridorState::ObserveL
+    /// What the agent sees in state
+    pub fn observe(state: TCorridorState) -> TCorridorObservation {
+        use TCorridorPosition::*;
+        match (state.position, state.trap) {
+            (Start, _) => TCorridorObservation::Start,
+            (Cue, Trap::Upper) => TCorridorObservation::ObserveU,
+            (Cue, Trap::Lower) => TCorridorObservation::ObserveL,
+            (Corridor(n), _) => TCorridorObservation::Corridor(n),
+            (Split, _) => TCorridorObservation::Split,
+            (Terminal, _) => TCorridorObservation::Terminal,
         }
     }
 
-    fn split_or_corridor(&self) -> TCorridorState {
-        use TCorridorState::*;
+    fn split_or_corridor(&self) -> TCorridorPosition {
+        use TCorridorPosition::*;
         if self.normal_steps > 0 {
             Corridor(1)
         } else {
@@ -66,28 +137,27 @@ impl TCorridor {
 
 impl Environment for TCorridor {
     type Action = TCorridorAction;
+    type Observation = TCorridorObservation;
     type State = TCorridorState;
 
-    fn take_action(&mut self, action: Self::Action) -> Option<(Self::State, Reward)> {
-        let default_reward: Reward = -5.0;
-        let trap_reward: Reward = -100.0;
-        let nontrap_reward: Reward = 100.0;
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        let default_reward = self.config.default_reward;
+        let trap_reward = self.config.trap_reward;
+        let nontrap_reward = self.config.nontrap_reward;
 
         if self.terminated() {
-            return None;
+            return Err(Error::EpisodeTerminated);
         }
 
         use TCorridorAction::*;
-        use TCorridorState::*;
-        let (next_state, reward) = match (self.current_state, action) {
-            (Start, TCorridorAction::Forward) => (self.observe(), default_reward),
+        use TCorridorPosition::*;
+        let trap = self.state.trap;
+        let (next_position, reward) = match (self.state.position, action) {
+            (Start, Forward) => (Cue, default_reward),
             (Start, _) => (Start, default_reward),
-            (ObserveL, Forward) => (self.split_or_corridor(), default_reward),
-            (ObserveL, Backward) => (Start, default_reward),
-            (ObserveL, _) => (ObserveL, default_reward),
-            (ObserveU, Forward) => (self.split_or_corridor(), default_reward),
-            (ObserveU, Backward) => (Start, default_reward),
-            (ObserveU, _) => (ObserveU, default_reward),
+            (Cue, Forward) => (self.split_or_corridor(), default_reward),
+            (Cue, Backward) => (Start, default_reward),
+            (Cue, _) => (Cue, default_reward),
             (Corridor(n), Forward) => {
                 if n == self.normal_steps {
                     (Split, default_reward)
@@ -98,7 +168,7 @@ impl Environment for TCorridor {
             (Corridor(n), _) => (Corridor(n), default_reward),
             (Split, Up) => (
                 Terminal,
-                if self.observed == 1 {
+                if trap == Trap::Upper {
                     trap_reward
                 } else {
                     nontrap_reward
@@ -106,61 +176,130 @@ impl Environment for TCorridor {
             ),
             (Split, Down) => (
                 Terminal,
-                if self.observed == -1 {
+                if trap == Trap::Lower {
                     trap_reward
                 } else {
                     nontrap_reward
                 },
             ),
-            (Split, Backward) => (
-                if self.observed == 1 {
-                    ObserveU
-                } else {
-                    ObserveL
-                },
-                default_reward,
-            ),
+            (Split, Backward) => (Cue, default_reward),
             (Split, _) => (Split, default_reward),
-            (Terminal, _) => panic!("action attempted in terminal"),
+            (Terminal, _) => return Err(Error::EpisodeTerminated),
         };
 
-        self.current_state = next_state;
-        Some((next_state, reward))
+        self.state.position = next_position;
+        Ok((TCorridor::observe(self.state), reward))
     }
 
-    fn available_actions(&self, state: Self::State) -> Vec<Self::Action> {
+    fn available_actions(&self, observation: Self::Observation) -> Vec<Self::Action> {
         use TCorridorAction::*;
-        use TCorridorState::*;
+        use TCorridorObservation::*;
 
-        match state {
-            // Start => vec![Forward],
-            // ObserveL => vec![Forward, Backward],
-            // ObserveU => vec![Forward, Backward],
-            // Corridor(_) => vec![Forward, Backward],
-            // Split => vec![Up, Down, Backward],
-            // Terminal => vec![Forward],
-            Start => vec![Forward],
-            ObserveL => vec![Forward],
-            ObserveU => vec![Forward],
-            Corridor(_) => vec![Forward],
+        let mut actions = match observation {
             Split => vec![Up, Down],
-            Terminal => vec![Forward],
+            _ => vec![Forward],
+        };
+
+        match observation {
+            ObserveL | ObserveU | Corridor(_) | Split if self.config.backward => {
+                actions.push(Backward)
+            }
+            _ => (),
         }
+
+        match observation {
+            Start | ObserveL | ObserveU | Corridor(_) if self.config.sideways => {
+                actions.push(Up);
+                actions.push(Down);
+            }
+            _ => (),
+        }
+
+        actions
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        TCorridor::observe(self.state)
     }
 
     fn current_state(&self) -> Self::State {
-        self.current_state
+        self.state
     }
 
     fn terminated(&self) -> bool {
-        self.current_state == TCorridorState::Terminal
+        self.state.position == TCorridorPosition::Terminal
+    }
+
+    fn is_terminal(&self, observation: Self::Observation) -> bool {
+        observation == TCorridorObservation::Terminal
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        Some(TCorridorObservation::Terminal)
+    }
+}
+
+/// Observations in corridor order, Start first and Terminal last
+#[derive(Clone, Copy, Debug)]
+pub struct TCorridorIndexing {
+    max_steps: usize, // longest corridor, Split and Terminal come after it
+}
+
+const ACTIONS: [TCorridorAction; 4] = [
+    TCorridorAction::Forward,
+    TCorridorAction::Backward,
+    TCorridorAction::Up,
+    TCorridorAction::Down,
+];
+
+impl Indexing<TCorridorObservation, TCorridorAction> for TCorridorIndexing {
+    fn states(&self) -> usize {
+        self.max_steps + 5
+    }
+
+    fn actions(&self) -> usize {
+        ACTIONS.len()
     }
 
-    fn is_terminal(&self, state: Self::State) -> bool {
-        state == TCorridorState::Terminal
+    fn state_index(&self, state: TCorridorObservation) -> usize {
+        use TCorridorObservation::*;
+        match state {
+            Start => 0,
+            ObserveU => 1,
+            ObserveL => 2,
+            Corridor(n) => {
+                // a longer corridor would collide with Split and Terminal
+                debug_assert!(
+                    (1..=self.max_steps).contains(&n),
+                    "Corridor({}) outside an indexing of {} steps",
+                    n,
+                    self.max_steps
+                );
+                n + 2 // n starts at 1
+            }
+            Split => self.max_steps + 3,
+            Terminal => self.max_steps + 4,
+        }
+    }
+
+    fn action_index(&self, action: TCorridorAction) -> usize {
+        action as usize
+    }
+
+    fn state(&self, index: usize) -> TCorridorObservation {
+        use TCorridorObservation::*;
+        debug_assert!(index < self.states(), "state index {} out of range", index);
+        match index {
+            0 => Start,
+            1 => ObserveU,
+            2 => ObserveL,
+            n if n <= self.max_steps + 2 => Corridor(n - 2),
+            n if n == self.max_steps + 3 => Split,
+            _ => Terminal,
+        }
     }
 
-    fn get_terminal(&self) -> Self::State {
-        TCorridorState::Terminal
+    fn action(&self, index: usize) -> TCorridorAction {
+        ACTIONS[index]
     }
 }
@@ -0,0 +1,259 @@
+use super::{Environment, Reward};
+use crate::{random, Error, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_CUES: usize = 8;
+
+/// How the sequence of cues determines the safe exit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CueCombination {
+    /// Only the first cue matters, later ones act as distractors
+    First,
+    /// Sum of all cues modulo the number of exits (XOR for two exits)
+    Sum,
+}
+
+#[derive(Clone, Debug)]
+pub struct TMazeConfig {
+    pub exits: usize, // K, also the number of distinct cue values
+    pub cues: usize,  // cues shown one after another at the start
+    pub combination: CueCombination,
+    pub normal_steps: usize,
+    pub distractors: usize,          // distinct distractor symbols
+    pub distractor_probability: f32, // chance that a corridor step shows a distractor
+    pub default_reward: Reward,
+    pub safe_reward: Reward,
+    pub trap_reward: Reward,
+}
+
+impl TMazeConfig {
+    /// Same task as `TCorridor`: two exits and a single cue
+    pub fn new(normal_steps: usize) -> TMazeConfig {
+        TMazeConfig {
+            exits: 2,
+            cues: 1,
+            combination: CueCombination::Sum,
+            normal_steps,
+            distractors: 0,
+            distractor_probability: 0.,
+            default_reward: -5.0,
+            safe_reward: 100.0,
+            trap_reward: -100.0,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.exits < 2 || self.exits > u8::MAX as usize {
+            return Err(Error::InvalidConfig(format!(
+                "T-maze needs 2 to {} exits, got {}",
+                u8::MAX,
+                self.exits
+            )));
+        }
+        if self.cues == 0 || self.cues > MAX_CUES {
+            return Err(Error::InvalidConfig(format!(
+                "T-maze needs 1 to {} cues, got {}",
+                MAX_CUES, self.cues
+            )));
+        }
+        if self.distractors > u8::MAX as usize {
+            return Err(Error::InvalidConfig(format!(
+                "at most {} distractors are supported",
+                u8::MAX
+            )));
+        }
+        Ok(())
+    }
+}
+
+// State representation exposed to the agent
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TMazeObservation {
+    Start,
+    Cue(u8),
+    Corridor(usize),
+    Distractor(u8), // shown instead of a corridor step
+    Junction,
+    Terminal,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TMazePosition {
+    Start,
+    Cue(usize), // index into the cue sequence
+    Corridor(usize),
+    Junction,
+    Terminal,
+}
+
+/// Full state, including the whole cue sequence
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TMazeState {
+    pub position: TMazePosition,
+    pub cues: [u8; MAX_CUES],
+    pub distractor: Option<u8>, // shown at the current corridor step
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TMazeAction {
+    Forward,
+    Exit(u8),
+}
+
+/// Generalization of `TCorridor` to K exits, sequences of cues and distractor observations
+pub struct TMaze {
+    config: TMazeConfig,
+    state: TMazeState,
+}
+
+impl TMaze {
+    pub fn new(config: TMazeConfig) -> Result<TMaze> {
+        config.validate()?;
+
+        let mut rng = random::rng();
+        let cues: Vec<u8> = (0..config.cues)
+            .map(|_| rng.gen_range(0, config.exits) as u8)
+            .collect();
+
+        TMaze::with_cues(config, &cues)
+    }
+
+    /// Maze showing the given cue sequence instead of a random one
+    pub fn with_cues(config: TMazeConfig, sequence: &[u8]) -> Result<TMaze> {
+        config.validate()?;
+        if sequence.len() != config.cues || sequence.iter().any(|cue| *cue as usize >= config.exits)
+        {
+            return Err(Error::InvalidConfig(format!(
+                "cues {:?} don't fit {} cues for {} exits",
+                sequence, config.cues, config.exits
+            )));
+        }
+
+        let mut cues = [0; MAX_CUES];
+        cues[..sequence.len()].copy_from_slice(sequence);
+
+        Ok(TMaze {
+            config,
+            state: TMazeState {
+                position: TMazePosition::Start,
+                cues,
+                distractor: None,
+            },
+        })
+    }
+
+    pub fn safe_exit(&self) -> u8 {
+        let cues = &self.state.cues[..self.config.cues];
+        match self.config.combination {
+            CueCombination::First => cues[0],
+            CueCombination::Sum => {
+                let sum: usize = cues.iter().map(|cue| *cue as usize).sum();
+                (sum % self.config.exits) as u8
+            }
+        }
+    }
+
+    /// What the agent sees in state
+    pub fn observe(state: TMazeState) -> TMazeObservation {
+        match state.position {
+            TMazePosition::Start => TMazeObservation::Start,
+            TMazePosition::Cue(i) => TMazeObservation::Cue(state.cues[i]),
+            TMazePosition::Corridor(n) => match state.distractor {
+                Some(d) => TMazeObservation::Distractor(d),
+                None => TMazeObservation::Corridor(n),
+            },
+            TMazePosition::Junction => TMazeObservation::Junction,
+            TMazePosition::Terminal => TMazeObservation::Terminal,
+        }
+    }
+
+    fn corridor_or_junction(&self, n: usize) -> TMazePosition {
+        if n <= self.config.normal_steps {
+            TMazePosition::Corridor(n)
+        } else {
+            TMazePosition::Junction
+        }
+    }
+
+    fn draw_distractor(&self) -> Option<u8> {
+        let mut rng = random::rng();
+        if self.config.distractors > 0 && rng.gen::<f32>() < self.config.distractor_probability {
+            Some(rng.gen_range(0, self.config.distractors) as u8)
+        } else {
+            None
+        }
+    }
+}
+
+impl Environment for TMaze {
+    type Action = TMazeAction;
+    type Observation = TMazeObservation;
+    type State = TMazeState;
+
+    fn take_action(&mut self, action: Self::Action) -> Result<(Self::Observation, Reward)> {
+        use TMazePosition::*;
+        let default_reward = self.config.default_reward;
+
+        let (next_position, reward) = match (self.state.position, action) {
+            (Terminal, _) => return Err(Error::EpisodeTerminated),
+            (Start, TMazeAction::Forward) => (Cue(0), default_reward),
+            (Cue(i), TMazeAction::Forward) => {
+                if i + 1 < self.config.cues {
+                    (Cue(i + 1), default_reward)
+                } else {
+                    (self.corridor_or_junction(1), default_reward)
+                }
+            }
+            (Corridor(n), TMazeAction::Forward) => {
+                (self.corridor_or_junction(n + 1), default_reward)
+            }
+            (Junction, TMazeAction::Exit(exit)) => (
+                Terminal,
+                if exit == self.safe_exit() {
+                    self.config.safe_reward
+                } else {
+                    self.config.trap_reward
+                },
+            ),
+            (position, _) => (position, default_reward),
+        };
+
+        self.state.position = next_position;
+        self.state.distractor = match next_position {
+            Corridor(_) => self.draw_distractor(),
+            _ => None,
+        };
+
+        Ok((TMaze::observe(self.state), reward))
+    }
+
+    fn available_actions(&self, observation: Self::Observation) -> Vec<Self::Action> {
+        match observation {
+            TMazeObservation::Junction => (0..self.config.exits)
+                .map(|exit| TMazeAction::Exit(exit as u8))
+                .collect(),
+            _ => vec![TMazeAction::Forward],
+        }
+    }
+
+    fn current_observation(&self) -> Self::Observation {
+        TMaze::observe(self.state)
+    }
+
+    fn current_state(&self) -> Self::State {
+        self.state
+    }
+
+    fn terminated(&self) -> bool {
+        self.state.position == TMazePosition::Terminal
+    }
+
+    fn is_terminal(&self, observation: Self::Observation) -> bool {
+        observation == TMazeObservation::Terminal
+    }
+
+    fn get_terminal(&self) -> Option<Self::Observation> {
+        Some(TMazeObservation::Terminal)
+    }
+}
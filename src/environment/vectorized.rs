@@ -0,0 +1,135 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use rayon::prelude::*;
+
+use super::{EnvTransition, Environment, Transition};
+use crate::learner::EpisodeStats;
+use crate::{random, Error, Result};
+
+struct Slot<E> {
+    env: E,
+    rng: Pcg64,          // the instance's own stream, independent of the worker thread
+    stats: EpisodeStats, // of the current episode
+}
+
+/// Many instances of an environment stepped in parallel. Finished episodes, terminated or cut
+/// off by a step limit, are replaced by fresh instances right away, so every slot always has an
+/// action to take.
+/// Each instance draws from its own generator, seeded from the creating thread's, so seeded
+/// runs give the same transitions however the steps are scheduled
+pub struct VecEnv<E: Environment, F: Fn() -> E> {
+    slots: Vec<Slot<E>>,
+    new: F,
+    finished: Vec<EpisodeStats>, // episodes completed since the last `take_finished`
+}
+
+impl<E, F> VecEnv<E, F>
+where
+    E: Environment + Send,
+    E::Observation: Send,
+    E::Action: Send + Sync,
+    F: Fn() -> E + Sync,
+{
+    pub fn new(instances: usize, new: F) -> VecEnv<E, F> {
+        let mut rng = random::rng();
+        let slots = (0..instances)
+            .map(|_| {
+                let mut slot_rng = Pcg64::seed_from_u64(rng.gen());
+                Slot {
+                    env: random::with_generator(&mut slot_rng, &new),
+                    rng: slot_rng,
+                    stats: EpisodeStats::new(),
+                }
+            })
+            .collect();
+
+        VecEnv {
+            slots,
+            new,
+            finished: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> &E {
+        &self.slots[i].env
+    }
+
+    pub fn observations(&self) -> Vec<E::Observation> {
+        self.slots
+            .iter()
+            .map(|slot| slot.env.current_observation())
+            .collect()
+    }
+
+    /// Takes actions[i] in instance i, returning the transitions in the same order.
+    /// Returns are discounted by gamma, and instances that terminate or reach max_steps are
+    /// reset before returning. Fails if the number of actions doesn't match the number of
+    /// instances
+    pub fn step(
+        &mut self,
+        actions: &[E::Action],
+        gamma: f32,
+        max_steps: Option<usize>,
+    ) -> Result<Vec<EnvTransition<E>>> {
+        if actions.len() != self.slots.len() {
+            return Err(Error::InvalidConfig(format!(
+                "{} actions for {} instances",
+                actions.len(),
+                self.slots.len()
+            )));
+        }
+        let new = &self.new;
+
+        let steps: Vec<Result<(EnvTransition<E>, Option<EpisodeStats>)>> = self
+            .slots
+            .par_iter_mut()
+            .zip(actions.par_iter())
+            .map(|(slot, action)| {
+                let Slot { env, rng, stats } = slot;
+                random::with_generator(rng, || {
+                    let state = env.current_observation();
+                    let (next_state, reward) = env.take_action(*action)?;
+                    let terminal = env.is_terminal(next_state);
+                    stats.record(reward, gamma, terminal);
+                    let transition = Transition {
+                        state,
+                        action: *action,
+                        reward,
+                        next_state,
+                        terminal,
+                    };
+
+                    let finished = if terminal || stats.truncate(max_steps) {
+                        *env = new();
+                        Some(std::mem::take(stats))
+                    } else {
+                        None
+                    };
+                    Ok((transition, finished))
+                })
+            })
+            .collect();
+
+        let mut transitions = Vec::with_capacity(steps.len());
+        for step in steps {
+            let (transition, finished) = step?;
+            transitions.push(transition);
+            self.finished.extend(finished);
+        }
+
+        Ok(transitions)
+    }
+
+    /// Episodes completed since the last call, in order of completion
+    pub fn take_finished(&mut self) -> Vec<EpisodeStats> {
+        std::mem::take(&mut self.finished)
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An action was taken after the episode had already ended
+    EpisodeTerminated,
+    /// The environment offers no actions in a non-terminal state (debug repr of the state)
+    NoAvailableActions(String),
+    /// A value estimate is NaN (debug repr of the state)
+    InvalidValue(String),
+    /// An environment or learner was configured with unusable parameters
+    InvalidConfig(String),
+    /// A remote environment reported an error or broke the protocol
+    Remote(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EpisodeTerminated => write!(f, "action taken in a terminal state"),
+            Error::NoAvailableActions(state) => write!(f, "no actions available in {}", state),
+            Error::InvalidValue(state) => write!(f, "NaN value estimate in {}", state),
+            Error::InvalidConfig(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::Remote(reason) => write!(f, "remote environment: {}", reason),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Json(e) => write!(f, "JSON error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
@@ -1,50 +1,129 @@
-use crate::environment::{Environment, Reward};
+use crate::environment::vectorized::VecEnv;
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::{random, Error, Result};
 use rand::Rng;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
+pub mod actor_critic;
+pub mod differential;
 pub mod dyna_q;
+pub mod experience_replay;
+pub mod n_step;
 pub mod n_step_sarsa;
+pub mod observer;
+pub mod off_policy_n_step_sarsa;
+pub mod policy;
 pub mod q_learning;
+pub mod q_sigma;
+pub mod q_table;
+pub mod reinforce;
 pub mod sarsa;
+pub mod semi_gradient;
+pub mod stats;
+pub mod tree_backup;
 
+pub use self::actor_critic::ActorCritic;
+pub use self::differential::{ContinuingLearner, DifferentialSarsa, RLearning};
 pub use self::dyna_q::DynaQ;
+pub use self::experience_replay::ExperienceReplay;
+pub use self::n_step::{NStepHistory, NStepLearner};
 pub use self::n_step_sarsa::NStepSarsa;
+pub use self::observer::{NoObserver, Printer, StepObserver, ValueUpdate};
+pub use self::off_policy_n_step_sarsa::OffPolicyNStepSarsa;
+pub use self::policy::{PolicyLearner, PolicyLearnerConfig};
 pub use self::q_learning::QLearning;
+pub use self::q_sigma::{QSigma, SigmaSchedule};
+pub use self::q_table::QTable;
+pub use self::reinforce::Reinforce;
 pub use self::sarsa::Sarsa;
+pub use self::semi_gradient::{ApproximateLearner, SemiGradientQLearning, SemiGradientSarsa};
+pub use self::stats::EpisodeStats;
+pub use self::tree_backup::TreeBackup;
 
 pub struct TabularLearnerData<E: Environment> {
-    pub q: HashMap<(E::State, E::Action), Reward>,
-    pub terminal_state: E::State,
+    pub q: QTable<E::Observation, E::Action>,
+    pub terminal_state: Option<E::Observation>, // None for continuing tasks
 }
 
 impl<E: Environment> TabularLearnerData<E> {
-    pub fn new(terminal_state: E::State) -> TabularLearnerData<E> {
+    pub fn new(terminal_state: Option<E::Observation>) -> TabularLearnerData<E> {
         TabularLearnerData {
-            q: HashMap::new(),
+            q: QTable::new(),
             terminal_state,
         }
     }
 
-    fn value(&self, config: &TabularLearnerConfig, state: E::State, action: E::Action) -> Reward {
-        if state == self.terminal_state {
+    pub fn value(
+        &self,
+        config: &TabularLearnerConfig,
+        state: E::Observation,
+        action: E::Action,
+    ) -> Reward {
+        if Some(state) == self.terminal_state {
             0.
         } else {
             *self.q.get(&(state, action)).unwrap_or(&config.initial_q)
         }
     }
 
-    fn set_value(&mut self, state: E::State, action: E::Action, value: Reward) {
+    fn set_value(&mut self, state: E::Observation, action: E::Action, value: Reward) {
         self.q.insert((state, action), value);
     }
 }
 
-#[derive(Clone)]
+// floats are stored exactly, for checkpoints
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TabularLearnerConfig {
-    pub alpha: f32,    // learning rate
-    pub epsilon: f32,  // epsilon-greedy
-    pub gamma: f32,    // discount factor
-    pub debug: bool,   // print episode steps
+    #[serde(with = "crate::checkpoint::exact")]
+    pub alpha: f32, // learning rate
+    #[serde(with = "crate::checkpoint::exact")]
+    pub epsilon: f32, // epsilon-greedy
+    #[serde(with = "crate::checkpoint::exact")]
+    pub gamma: f32, // discount factor
+    #[serde(with = "crate::checkpoint::exact")]
     initial_q: Reward, // default value
+    #[serde(default)]
+    pub tie_breaking: TieBreaking, // among equally valued greedy actions
+    pub max_steps: Option<usize>, // episodes longer than this are truncated
+}
+
+/// Which of several maximal actions greedy selection picks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreaking {
+    /// The first in `available_actions` order. Deterministic, but biased towards that action
+    /// for as long as values are tied, e.g. from `initial_q`
+    #[default]
+    First,
+    /// Uniformly at random
+    Random,
+}
+
+impl TieBreaking {
+    /// Best of the (action, value) pairs, None if there are none. Values must not be NaN
+    pub fn argmax<A>(self, values: impl IntoIterator<Item = (A, Reward)>) -> Option<(A, Reward)> {
+        let mut rng = random::rng();
+        let mut best: Option<(A, Reward)> = None;
+        let mut ties = 0; // seen so far with the best value
+
+        for (action, value) in values {
+            match best {
+                Some((_, best_value)) if value < best_value => {}
+                Some((_, best_value)) if value == best_value => {
+                    ties += 1;
+                    // keeps each of the tied actions with probability 1 / ties
+                    if self == TieBreaking::Random && rng.gen_range(0, ties) == 0 {
+                        best = Some((action, value));
+                    }
+                }
+                _ => {
+                    best = Some((action, value));
+                    ties = 1;
+                }
+            }
+        }
+
+        best
+    }
 }
 
 impl TabularLearnerConfig {
@@ -53,51 +132,232 @@ impl TabularLearnerConfig {
             alpha,
             epsilon,
             gamma,
-            debug: false,
             initial_q,
+            tie_breaking: TieBreaking::First,
+            max_steps: None,
         }
     }
 }
 
 pub trait TabularLearner<E: Environment> {
-    fn episode(&mut self, env: &mut E) -> Reward;
+    /// Runs an episode, reporting steps and value updates to the observer
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats>;
+    fn episode(&mut self, env: &mut E) -> Result<EpisodeStats> {
+        self.episode_with(env, &mut NoObserver)
+    }
     fn data(&self) -> &TabularLearnerData<E>;
     fn data_mut(&mut self) -> &mut TabularLearnerData<E>;
     fn config(&self) -> &TabularLearnerConfig;
     fn config_mut(&mut self) -> &mut TabularLearnerConfig;
-    fn update(&mut self, alpha: f32, state: E::State, action: E::Action, target: Reward) {
+    /// Moves Q(state, action) towards target, returning the new value
+    fn update(
+        &mut self,
+        alpha: f32,
+        state: E::Observation,
+        action: E::Action,
+        target: Reward,
+    ) -> Reward {
         let current_value = self.data().value(self.config(), state, action);
-        self.data_mut().set_value(
+        let value = current_value + alpha * (target - current_value);
+        self.data_mut().set_value(state, action, value);
+        value
+    }
+
+    fn observed_update(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        alpha: f32,
+        state: E::Observation,
+        action: E::Action,
+        target: Reward,
+    ) {
+        let value = self.update(alpha, state, action, target);
+        observer.on_update(&ValueUpdate {
             state,
             action,
-            current_value + alpha * (target - current_value),
-        );
+            target,
+            value,
+        });
     }
 
-    fn epsilon_greedy(&self, epsilon: f32, from: E::State, env: &E) -> E::Action {
-        let available = env.available_actions(from);
-        let mut with_values: Vec<(E::Action, Reward)> = available
-            .iter()
-            .map(|action| (*action, self.data().value(self.config(), from, *action)))
-            .collect();
-        with_values.sort_by(|(_, v1), (_, v2)| v2.partial_cmp(v1).unwrap());
+    /// Offline one-step updates from a recorded trajectory, using the Q-learning target.
+    /// env is only consulted for the available actions.
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        one_step_updates(self, trajectory, env)
+    }
+
+    /// Learns from transitions collected in parallel, e.g. by a `VecEnv`. Unlike `replay`,
+    /// consecutive transitions may come from different episodes, so by default every one gets
+    /// its own one-step update towards `q_target` (the Q-learning target). Learners whose
+    /// updates need whole trajectories fail with `Error::InvalidConfig`
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        one_step_updates(self, batch, env)
+    }
+
+    /// One-step Q-learning target R + gamma * max_a Q(S', a)
+    fn q_target(&self, transition: &EnvTransition<E>, env: &E) -> Result<Reward> {
+        let next_value = if transition.terminal {
+            0.
+        } else {
+            self.max_action_value(transition.next_state, env)?
+        };
+        Ok(transition.reward + self.config().gamma * next_value)
+    }
+
+    /// Best available action with its value, ties broken by the config's rule
+    fn greedy(&self, state: E::Observation, env: &E) -> Result<(E::Action, Reward)> {
+        best(self, state, env, self.config().tie_breaking)
+    }
 
-        let mut rng = rand::thread_rng();
+    fn epsilon_greedy(&self, epsilon: f32, from: E::Observation, env: &E) -> Result<E::Action> {
+        let mut rng = random::rng();
 
         if rng.gen::<f32>() < epsilon {
-            with_values[rng.gen_range(0, with_values.len())].0
+            let available = env.available_actions(from);
+            if available.is_empty() {
+                return Err(Error::NoAvailableActions(format!("{:?}", from)));
+            }
+            Ok(available[rng.gen_range(0, available.len())])
         } else {
-            with_values[0].0
+            Ok(self.greedy(from, env)?.0)
         }
     }
 
-    fn max_action_value(&self, state: E::State, env: &E) -> f32 {
+    /// Probability of epsilon_greedy(epsilon, state) choosing action
+    fn epsilon_greedy_probability(
+        &self,
+        epsilon: f32,
+        state: E::Observation,
+        action: E::Action,
+        env: &E,
+    ) -> Result<f32> {
         let available = env.available_actions(state);
-        let mut with_values: Vec<(E::Action, Reward)> = available
+        if available.is_empty() {
+            return Err(Error::NoAvailableActions(format!("{:?}", state)));
+        }
+        let explore = epsilon / available.len() as f32;
+        let greedy = match self.config().tie_breaking {
+            TieBreaking::First => {
+                if self.greedy(state, env)?.0 == action {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            TieBreaking::Random => {
+                let max = self.max_action_value(state, env)?;
+                if self.data().value(self.config(), state, action) == max {
+                    let ties = available
+                        .iter()
+                        .filter(|a| self.data().value(self.config(), state, **a) == max)
+                        .count();
+                    1. / ties as f32
+                } else {
+                    0.
+                }
+            }
+        };
+
+        Ok((1. - epsilon) * greedy + explore)
+    }
+
+    /// Expected value of Q(state, A) with A chosen by epsilon_greedy(epsilon, state)
+    fn epsilon_greedy_value(&self, epsilon: f32, state: E::Observation, env: &E) -> Result<Reward> {
+        let available = env.available_actions(state);
+        if available.is_empty() {
+            return Err(Error::NoAvailableActions(format!("{:?}", state)));
+        }
+        let (_, greedy_value) = self.greedy(state, env)?;
+        let explore = epsilon / available.len() as f32;
+        let mean: Reward = available
             .iter()
-            .map(|action| (*action, self.data().value(self.config(), state, *action)))
-            .collect();
-        with_values.sort_by(|(_, v1), (_, v2)| v2.partial_cmp(v1).unwrap());
-        with_values[0].1
+            .map(|action| explore * self.data().value(self.config(), state, *action))
+            .sum();
+
+        Ok(mean + (1. - epsilon) * greedy_value)
+    }
+
+    fn max_action_value(&self, state: E::Observation, env: &E) -> Result<Reward> {
+        // the value doesn't depend on which maximal action is picked
+        Ok(best(self, state, env, TieBreaking::First)?.1)
+    }
+}
+
+// default of both `replay` and `learn_batch`, so overriding one doesn't change the other
+fn one_step_updates<E: Environment, L: TabularLearner<E> + ?Sized>(
+    learner: &mut L,
+    transitions: &[EnvTransition<E>],
+    env: &E,
+) -> Result<()> {
+    learner.data_mut().terminal_state = env.get_terminal();
+
+    for transition in transitions {
+        let target = learner.q_target(transition, env)?;
+        learner.update(
+            learner.config().alpha,
+            transition.state,
+            transition.action,
+            target,
+        );
     }
+
+    Ok(())
+}
+
+fn best<E: Environment, L: TabularLearner<E> + ?Sized>(
+    learner: &L,
+    state: E::Observation,
+    env: &E,
+    tie_breaking: TieBreaking,
+) -> Result<(E::Action, Reward)> {
+    let mut invalid = false;
+    let values = env.available_actions(state).into_iter().map(|action| {
+        let value = learner.data().value(learner.config(), state, action);
+        invalid |= value.is_nan();
+        (action, value)
+    });
+    let best = tie_breaking.argmax(values);
+
+    if invalid {
+        return Err(Error::InvalidValue(format!("{:?}", state)));
+    }
+    best.ok_or_else(|| Error::NoAvailableActions(format!("{:?}", state)))
+}
+
+/// Collects `steps` batches of transitions from envs, choosing actions epsilon-greedily and
+/// passing each batch to `learn_batch`. Returns the episodes finished meanwhile, truncated at
+/// the config's max_steps. How a batch is learned from depends on the learner, see `learn_batch`
+pub fn run_batched<E, F, L>(
+    learner: &mut L,
+    envs: &mut VecEnv<E, F>,
+    steps: usize,
+) -> Result<Vec<EpisodeStats>>
+where
+    E: Environment + Send,
+    E::Observation: Send,
+    E::Action: Send + Sync,
+    F: Fn() -> E + Sync,
+    L: TabularLearner<E> + ?Sized,
+{
+    if envs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    for _ in 0..steps {
+        let actions = envs
+            .observations()
+            .into_iter()
+            .enumerate()
+            .map(|(i, state)| learner.epsilon_greedy(learner.config().epsilon, state, envs.get(i)))
+            .collect::<Result<Vec<_>>>()?;
+        let config = learner.config();
+        let batch = envs.step(&actions, config.gamma, config.max_steps)?;
+        learner.learn_batch(&batch, envs.get(0))?;
+    }
+
+    Ok(envs.take_finished())
 }
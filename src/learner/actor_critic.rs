@@ -0,0 +1,89 @@
+use crate::environment::{Environment, Transition};
+pub use crate::learner::policy::{PolicyLearner, PolicyLearnerConfig, SoftmaxPolicy, StateValues};
+use crate::learner::{EpisodeStats, StepObserver};
+use crate::Result;
+
+/// One-step actor-critic with a tabular softmax actor and state-value critic
+pub struct ActorCritic<E: Environment> {
+    pub config: PolicyLearnerConfig,
+    policy: SoftmaxPolicy<E>,
+    critic: StateValues<E>,
+}
+
+impl<E: Environment> ActorCritic<E> {
+    pub fn new(
+        config: PolicyLearnerConfig,
+        terminal_state: Option<E::Observation>,
+    ) -> ActorCritic<E> {
+        ActorCritic {
+            config,
+            policy: SoftmaxPolicy::new(),
+            critic: StateValues::new(terminal_state),
+        }
+    }
+
+    pub fn critic(&self) -> &StateValues<E> {
+        &self.critic
+    }
+}
+
+impl<E: Environment> PolicyLearner<E> for ActorCritic<E> {
+    // env is preinitialized
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.critic.terminal_state = env.get_terminal();
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+        let mut discount = 1.; // gamma^t
+
+        loop {
+            let action = self.policy.sample(state, env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config.gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+
+            let target = reward + self.config.gamma * self.critic.value(next_state);
+            let delta = target - self.critic.value(state);
+            self.critic.update(self.config.beta, state, target);
+            self.policy.step(
+                observer,
+                self.config.alpha * discount * delta,
+                target,
+                state,
+                action,
+                env,
+            )?;
+            discount *= self.config.gamma;
+
+            if terminal || stats.truncate(self.config.max_steps) {
+                break;
+            }
+            state = next_state;
+        }
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn policy(&self) -> &SoftmaxPolicy<E> {
+        &self.policy
+    }
+
+    fn config(&self) -> &PolicyLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut PolicyLearnerConfig {
+        &mut self.config
+    }
+}
@@ -0,0 +1,338 @@
+use crate::environment::{EnvTransition, Environment, Reward, Transition};
+use crate::learner::{EpisodeStats, NoObserver};
+pub use crate::learner::{StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use crate::Result;
+
+/// Average-reward learners for continuing tasks. Values are differential, gamma is unused
+pub trait ContinuingLearner<E: Environment>: TabularLearner<E> {
+    /// Runs for a number of steps, or until an episodic environment terminates.
+    /// Stopping after `steps` counts as truncation
+    fn run_with(
+        &mut self,
+        env: &mut E,
+        steps: usize,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats>;
+    fn run(&mut self, env: &mut E, steps: usize) -> Result<EpisodeStats> {
+        self.run_with(env, steps, &mut NoObserver)
+    }
+    /// Current estimate of the average reward per step
+    fn average_reward(&self) -> Reward;
+}
+
+/// Differential one-step target R - avg + max_a q(S', a)
+fn differential_target<E: Environment, L: TabularLearner<E>>(
+    learner: &L,
+    average_reward: Reward,
+    transition: &EnvTransition<E>,
+    env: &E,
+) -> Result<Reward> {
+    let next_value = if transition.terminal {
+        0.
+    } else {
+        learner.max_action_value(transition.next_state, env)?
+    };
+    Ok(transition.reward - average_reward + next_value)
+}
+
+/// Differential semi-gradient SARSA, tabular (pg. 251 of <book>)
+pub struct DifferentialSarsa<E: Environment> {
+    pub config: TabularLearnerConfig,
+    pub beta: f32, // average reward step size
+    data: TabularLearnerData<E>,
+    average_reward: Reward,
+}
+
+impl<E: Environment> DifferentialSarsa<E> {
+    pub fn new(
+        config: TabularLearnerConfig,
+        beta: f32,
+        terminal_state: Option<E::Observation>,
+    ) -> DifferentialSarsa<E> {
+        DifferentialSarsa {
+            config,
+            beta,
+            data: TabularLearnerData::new(terminal_state),
+            average_reward: 0.,
+        }
+    }
+
+    // moves Q(state, action) and the average reward by the differential error of
+    // R - avg + q(S', A')
+    fn learn(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        transition: &EnvTransition<E>,
+        next_value: Reward,
+    ) {
+        let target = transition.reward - self.average_reward + next_value;
+        let delta = target
+            - self
+                .data
+                .value(&self.config, transition.state, transition.action);
+        self.average_reward += self.beta * delta;
+        self.observed_update(
+            observer,
+            self.config.alpha,
+            transition.state,
+            transition.action,
+            target,
+        );
+    }
+}
+
+impl<E: Environment> ContinuingLearner<E> for DifferentialSarsa<E> {
+    // env is preinitialized
+    fn run_with(
+        &mut self,
+        env: &mut E,
+        steps: usize,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.data.terminal_state = env.get_terminal();
+        let mut state = env.current_observation();
+        let mut action = self.epsilon_greedy(self.config.epsilon, state, env)?;
+        let mut stats = EpisodeStats::new();
+
+        while !stats.truncate(Some(steps)) {
+            let (next_state, reward) = env.take_action(action)?;
+            let transition = Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal: env.is_terminal(next_state),
+            };
+            stats.record(reward, self.config.gamma, transition.terminal);
+            observer.on_step(&transition);
+
+            let (next_action, next_value) = if transition.terminal {
+                (None, 0.)
+            } else {
+                let next_action = self.epsilon_greedy(self.config.epsilon, next_state, env)?;
+                let value = self.data.value(&self.config, next_state, next_action);
+                (Some(next_action), value)
+            };
+            self.learn(observer, &transition, next_value);
+
+            match next_action {
+                Some(next_action) => {
+                    state = next_state;
+                    action = next_action;
+                }
+                None => break,
+            }
+        }
+
+        Ok(stats)
+    }
+
+    fn average_reward(&self) -> Reward {
+        self.average_reward
+    }
+}
+
+impl<E: Environment> TabularLearner<E> for DifferentialSarsa<E> {
+    /// Never returns on continuing tasks without max_steps, use `run` instead
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        let steps = self.config.max_steps.unwrap_or(usize::MAX);
+        let stats = self.run_with(env, steps, observer)?;
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    // uses the recorded A_{t+1}, updating the average reward like `run`
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for (i, transition) in trajectory.iter().enumerate() {
+            let next_value = match trajectory.get(i + 1) {
+                _ if transition.terminal => 0.,
+                Some(next) => self.data.value(&self.config, next.state, next.action),
+                None => break, // A_{t+1} was not recorded
+            };
+            self.learn(&mut NoObserver, transition, next_value);
+        }
+
+        Ok(())
+    }
+
+    // A_{t+1} isn't known for batched transitions, so the expectation under the learner's own
+    // epsilon-greedy policy stands in for it, as in `Sarsa`
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for transition in batch {
+            let next_value = if transition.terminal {
+                0.
+            } else {
+                self.epsilon_greedy_value(self.config.epsilon, transition.next_state, env)?
+            };
+            self.learn(&mut NoObserver, transition, next_value);
+        }
+
+        Ok(())
+    }
+
+    fn q_target(&self, transition: &EnvTransition<E>, env: &E) -> Result<Reward> {
+        differential_target(self, self.average_reward, transition, env)
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        &mut self.data
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
+
+/// Schwartz's R-learning, the off-policy differential counterpart of Q-learning.
+/// The average reward is only updated after greedy actions
+pub struct RLearning<E: Environment> {
+    pub config: TabularLearnerConfig,
+    pub beta: f32, // average reward step size
+    data: TabularLearnerData<E>,
+    average_reward: Reward,
+}
+
+impl<E: Environment> RLearning<E> {
+    pub fn new(
+        config: TabularLearnerConfig,
+        beta: f32,
+        terminal_state: Option<E::Observation>,
+    ) -> RLearning<E> {
+        RLearning {
+            config,
+            beta,
+            data: TabularLearnerData::new(terminal_state),
+            average_reward: 0.,
+        }
+    }
+
+    // one R-learning step, moving the average reward only if the action is still greedy
+    fn learn(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        transition: &EnvTransition<E>,
+        env: &E,
+    ) -> Result<()> {
+        let (state, action) = (transition.state, transition.action);
+        let target = differential_target(self, self.average_reward, transition, env)?;
+        self.observed_update(observer, self.config.alpha, state, action, target);
+        // greediness is checked after the update, as in the original algorithm
+        let greedy = self.max_action_value(state, env)?;
+        if self.data.value(&self.config, state, action) == greedy {
+            // R - avg + max q(S') - max q(S)
+            let error = differential_target(self, self.average_reward, transition, env)? - greedy;
+            self.average_reward += self.beta * error;
+        }
+
+        Ok(())
+    }
+
+    // transitions are independent, so replay and batches are learned from the same way
+    fn learn_transitions(&mut self, transitions: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for transition in transitions {
+            self.learn(&mut NoObserver, transition, env)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: Environment> ContinuingLearner<E> for RLearning<E> {
+    // env is preinitialized
+    fn run_with(
+        &mut self,
+        env: &mut E,
+        steps: usize,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.data.terminal_state = env.get_terminal();
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+
+        while !stats.truncate(Some(steps)) {
+            let action = self.epsilon_greedy(self.config.epsilon, state, env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let transition = Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal: env.is_terminal(next_state),
+            };
+            stats.record(reward, self.config.gamma, transition.terminal);
+            observer.on_step(&transition);
+            self.learn(observer, &transition, env)?;
+
+            if transition.terminal {
+                break;
+            }
+            state = next_state;
+        }
+
+        Ok(stats)
+    }
+
+    fn average_reward(&self) -> Reward {
+        self.average_reward
+    }
+}
+
+impl<E: Environment> TabularLearner<E> for RLearning<E> {
+    /// Never returns on continuing tasks without max_steps, use `run` instead
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        let steps = self.config.max_steps.unwrap_or(usize::MAX);
+        let stats = self.run_with(env, steps, observer)?;
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.learn_transitions(trajectory, env)
+    }
+
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.learn_transitions(batch, env)
+    }
+
+    fn q_target(&self, transition: &EnvTransition<E>, env: &E) -> Result<Reward> {
+        differential_target(self, self.average_reward, transition, env)
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        &mut self.data
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
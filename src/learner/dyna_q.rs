@@ -1,67 +1,152 @@
 use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::environment::{Environment, Reward};
-pub use crate::learner::{TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use crate::checkpoint::{Checkpointable, TabularState};
+use crate::environment::{EnvTransition, Environment, Reward, Transition};
+use crate::learner::NoObserver;
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::{random, Result};
+
+type Model<E> = HashMap<
+    (<E as Environment>::Observation, <E as Environment>::Action),
+    (<E as Environment>::Observation, Reward),
+>;
 
 pub struct DynaQ<E: Environment> {
     pub config: TabularLearnerConfig,
     data: TabularLearnerData<E>,
     n: u32, // planning steps (when planning is used, e.g. DynaQ)
-    model: HashMap<(E::State, E::Action), (E::State, Reward)>,
+    model: Model<E>,
+    observed: Vec<(E::Observation, E::Action)>, // model keys in insertion order, for seeded runs
 }
 
 impl<E: Environment> DynaQ<E> {
-    pub fn new(config: TabularLearnerConfig, n: u32, terminal_state: E::State) -> DynaQ<E> {
+    pub fn new(
+        config: TabularLearnerConfig,
+        n: u32,
+        terminal_state: Option<E::Observation>,
+    ) -> DynaQ<E> {
         let data = TabularLearnerData::new(terminal_state);
         DynaQ {
             config,
             data,
             n,
             model: HashMap::new(),
+            observed: Vec::new(),
+        }
+    }
+
+    fn remember(
+        &mut self,
+        state: E::Observation,
+        action: E::Action,
+        next_state: E::Observation,
+        reward: Reward,
+    ) {
+        if self
+            .model
+            .insert((state, action), (next_state, reward))
+            .is_none()
+        {
+            self.observed.push((state, action));
+        }
+    }
+
+    // n updates from transitions sampled from the model
+    fn plan(&mut self, env: &E, observer: &mut dyn StepObserver<E>) -> Result<()> {
+        let mut rng = random::rng();
+
+        for _ in 0..self.n {
+            let (model_state, model_action) = self.observed[rng.gen_range(0, self.observed.len())];
+            let (model_next_state, model_reward) = self.model[&(model_state, model_action)];
+            let target = self.q_target(
+                &Transition {
+                    state: model_state,
+                    action: model_action,
+                    reward: model_reward,
+                    next_state: model_next_state,
+                    terminal: env.is_terminal(model_next_state),
+                },
+                env,
+            )?;
+            self.observed_update(
+                observer,
+                self.config.alpha,
+                model_state,
+                model_action,
+                target,
+            );
         }
+
+        Ok(())
     }
 }
 
 impl<E: Environment> TabularLearner<E> for DynaQ<E> {
     // env is preinitialized
-    fn episode(&mut self, env: &mut E) -> Reward {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
         self.data.terminal_state = env.get_terminal();
-        let mut gain: Reward = 0.0;
+        let mut stats = EpisodeStats::new();
 
         loop {
-            let mut state = env.current_state();
-            let action = self.epsilon_greedy(self.config.epsilon, env.current_state(), env);
-            let (next_state, reward) = env.take_action(action).unwrap();
-            // episode() assumes gamma=1
-            gain += reward;
-            let target = reward + self.config.gamma * self.max_action_value(next_state, &env);
-            self.update(self.config.alpha, state, action, target);
-            self.model.insert((state, action), (next_state, reward));
-
-            let mut rng = rand::thread_rng();
-
-            for _ in 0..self.n {
-                let model_keys: Vec<(E::State, E::Action)> =
-                    self.model.keys().map(|a| *a).collect();
-                let (model_state, model_action) = model_keys[rng.gen_range(0, model_keys.len())];
-                let (model_next_state, model_reward) = self.model[&(model_state, model_action)];
-                let target = model_reward
-                    + self.config.gamma * self.max_action_value(model_next_state, &env);
-                self.update(self.config.alpha, model_state, model_action, target);
-            }
-
-            if self.config.debug {
-                println!("{:?} -> {:?}", state, next_state);
-            }
+            let mut state = env.current_observation();
+            let action =
+                self.epsilon_greedy(self.config.epsilon, env.current_observation(), env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let transition = Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal: env.is_terminal(next_state),
+            };
+            stats.record(reward, self.config.gamma, transition.terminal);
+            observer.on_step(&transition);
+            let target = self.q_target(&transition, env)?;
+            self.observed_update(observer, self.config.alpha, state, action, target);
+            self.remember(state, action, next_state, reward);
+            self.plan(env, observer)?;
 
             state = next_state;
-            if state == self.data.terminal_state {
+            if Some(state) == self.data.terminal_state || stats.truncate(self.config.max_steps) {
                 break;
             }
         }
 
-        gain
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    // every real transition is learned from and added to the model, then planning follows
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for transition in batch {
+            let target = self.q_target(transition, env)?;
+            self.update(
+                self.config.alpha,
+                transition.state,
+                transition.action,
+                target,
+            );
+            self.remember(
+                transition.state,
+                transition.action,
+                transition.next_state,
+                transition.reward,
+            );
+            self.plan(env, &mut NoObserver)?;
+        }
+
+        Ok(())
     }
 
     fn data(&self) -> &TabularLearnerData<E> {
@@ -80,3 +165,44 @@ impl<E: Environment> TabularLearner<E> for DynaQ<E> {
         &mut self.config
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DynaQState<S, A> {
+    pub tabular: TabularState<S, A>,
+    pub n: u32,
+    pub model: Vec<(S, A, S, u32)>, // insertion order, reward bits
+}
+
+impl<E: Environment> Checkpointable for DynaQ<E>
+where
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+{
+    type State = DynaQState<E::Observation, E::Action>;
+
+    fn save(&self) -> Self::State {
+        DynaQState {
+            tabular: TabularState::save(&self.config, &self.data),
+            n: self.n,
+            model: self
+                .observed
+                .iter()
+                .map(|(state, action)| {
+                    let (next_state, reward) = self.model[&(*state, *action)];
+                    (*state, *action, next_state, reward.to_bits())
+                })
+                .collect(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        state.tabular.restore(&mut self.config, &mut self.data);
+        self.n = state.n;
+        self.observed = state.model.iter().map(|(s, a, _, _)| (*s, *a)).collect();
+        self.model = state
+            .model
+            .into_iter()
+            .map(|(s, a, next, bits)| ((s, a), (next, f32::from_bits(bits))))
+            .collect();
+    }
+}
@@ -0,0 +1,242 @@
+use rand::Rng;
+use std::collections::VecDeque;
+
+use crate::environment::{EnvTransition, Environment, Reward, Transition};
+use crate::learner::NoObserver;
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::{random, Result};
+
+const MIN_PRIORITY: f32 = 1e-3; // keeps zero-error transitions replayable
+
+#[derive(Clone, Copy, Debug)]
+pub enum Sampling {
+    Uniform,
+    /// Proportional to |TD error|^alpha (without importance sampling correction)
+    Prioritized {
+        alpha: f32,
+    },
+}
+
+/// Bounded FIFO of transitions, dropping the oldest once full
+pub struct ReplayBuffer<S, A> {
+    capacity: usize,
+    sampling: Sampling,
+    transitions: VecDeque<Transition<S, A>>,
+    priorities: VecDeque<f32>,
+}
+
+impl<S: Copy, A: Copy> ReplayBuffer<S, A> {
+    pub fn new(capacity: usize, sampling: Sampling) -> ReplayBuffer<S, A> {
+        ReplayBuffer {
+            capacity,
+            sampling,
+            transitions: VecDeque::with_capacity(capacity),
+            priorities: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// New transitions get the highest priority seen so far, so each is replayed soon
+    pub fn push(&mut self, transition: Transition<S, A>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.transitions.len() == self.capacity {
+            self.transitions.pop_front();
+            self.priorities.pop_front();
+        }
+
+        let priority = self.priorities.iter().cloned().fold(1., f32::max);
+        self.transitions.push_back(transition);
+        self.priorities.push_back(priority);
+    }
+
+    /// Returns the index of a transition drawn according to the sampling scheme
+    pub fn sample(&self) -> Option<usize> {
+        if self.transitions.is_empty() {
+            return None;
+        }
+
+        let mut rng = random::rng();
+
+        match self.sampling {
+            Sampling::Uniform => Some(rng.gen_range(0, self.transitions.len())),
+            Sampling::Prioritized { alpha } => {
+                let total: f32 = self.priorities.iter().map(|p| p.powf(alpha)).sum();
+                let mut remaining = rng.gen::<f32>() * total;
+
+                for (i, priority) in self.priorities.iter().enumerate() {
+                    remaining -= priority.powf(alpha);
+                    if remaining < 0. {
+                        return Some(i);
+                    }
+                }
+
+                Some(self.transitions.len() - 1) // rounding
+            }
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Transition<S, A> {
+        self.transitions[i]
+    }
+
+    pub fn set_priority(&mut self, i: usize, td_error: Reward) {
+        self.priorities[i] = td_error.abs().max(MIN_PRIORITY);
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+}
+
+/// Attaches a replay buffer to an off-policy learner (e.g. QLearning). Every real transition gets a
+/// Q-learning update and is stored; `replay_ratio` stored transitions are then sampled per step,
+/// each getting another one-step update towards the learner's `q_target`.
+pub struct ExperienceReplay<E: Environment, L: TabularLearner<E>> {
+    learner: L,
+    buffer: ReplayBuffer<E::Observation, E::Action>,
+    replay_ratio: f32, // replayed transitions per environment step
+    owed: f32,         // fractional replays carried over between steps
+}
+
+impl<E: Environment, L: TabularLearner<E>> ExperienceReplay<E, L> {
+    pub fn new(
+        learner: L,
+        capacity: usize,
+        replay_ratio: f32,
+        sampling: Sampling,
+    ) -> ExperienceReplay<E, L> {
+        ExperienceReplay {
+            learner,
+            buffer: ReplayBuffer::new(capacity, sampling),
+            replay_ratio,
+            owed: 0.,
+        }
+    }
+
+    pub fn learner(&self) -> &L {
+        &self.learner
+    }
+
+    pub fn buffer(&self) -> &ReplayBuffer<E::Observation, E::Action> {
+        &self.buffer
+    }
+
+    // one-step update of a sampled transition, reported to the observer like any other
+    fn replay_sample(&mut self, env: &E, observer: &mut dyn StepObserver<E>) -> Result<()> {
+        if let Some(i) = self.buffer.sample() {
+            let transition = self.buffer.get(i);
+            let value = self.learner.data().value(
+                self.learner.config(),
+                transition.state,
+                transition.action,
+            );
+            let target = self.learner.q_target(&transition, env)?;
+            let alpha = self.learner.config().alpha;
+            self.learner.observed_update(
+                observer,
+                alpha,
+                transition.state,
+                transition.action,
+                target,
+            );
+            self.buffer.set_priority(i, target - value);
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: Environment, L: TabularLearner<E>> TabularLearner<E> for ExperienceReplay<E, L> {
+    // env is preinitialized
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.learner.data_mut().terminal_state = env.get_terminal();
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let state = env.current_observation();
+            let action = self
+                .learner
+                .epsilon_greedy(self.learner.config().epsilon, state, env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let transition = Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal: env.is_terminal(next_state),
+            };
+            stats.record(reward, self.learner.config().gamma, transition.terminal);
+            observer.on_step(&transition);
+            let target = self.learner.q_target(&transition, env)?;
+            let alpha = self.learner.config().alpha;
+            self.learner
+                .observed_update(observer, alpha, state, action, target);
+            self.buffer.push(transition);
+
+            self.owed += self.replay_ratio;
+            while self.owed >= 1. {
+                self.owed -= 1.;
+                self.replay_sample(env, observer)?;
+            }
+
+            if transition.terminal || stats.truncate(self.learner.config().max_steps) {
+                break;
+            }
+        }
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.learner.replay(trajectory, env)?;
+        for transition in trajectory {
+            self.buffer.push(*transition);
+        }
+        Ok(())
+    }
+
+    // the batch is learned from and stored, then replay_ratio samples are owed per transition
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.learner.learn_batch(batch, env)?;
+        for transition in batch {
+            self.buffer.push(*transition);
+        }
+
+        self.owed += self.replay_ratio * batch.len() as f32;
+        while self.owed >= 1. {
+            self.owed -= 1.;
+            self.replay_sample(env, &mut NoObserver)?;
+        }
+
+        Ok(())
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        self.learner.data()
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        self.learner.data_mut()
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        self.learner.config()
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        self.learner.config_mut()
+    }
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::environment::{Environment, Reward, Transition};
+use crate::learner::{EpisodeStats, StepObserver, TabularLearner};
+use crate::{Error, Result};
+
+/// A step (S_t, A_t, R_{t+1}) of an n-step window
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step<S, A> {
+    pub state: S,
+    pub action: A,
+    #[serde(with = "crate::checkpoint::exact")]
+    pub reward: Reward,
+    #[serde(with = "crate::checkpoint::exact")]
+    pub probability: f32, // b(A_t|S_t) under the behaviour policy when A_t was chosen
+}
+
+/// Last n steps of an episode, oldest first
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NStepHistory<S, A> {
+    n: usize,
+    steps: VecDeque<Step<S, A>>,
+    start: usize, // time step t of the oldest step
+}
+
+impl<S, A> NStepHistory<S, A> {
+    pub fn new(n: usize) -> NStepHistory<S, A> {
+        NStepHistory {
+            n,
+            steps: VecDeque::with_capacity(n),
+            start: 0,
+        }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.steps.len() >= self.n
+    }
+
+    pub fn get(&self, i: usize) -> &Step<S, A> {
+        &self.steps[i]
+    }
+
+    /// Time step of the oldest step, counted from the start of the episode
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn push(&mut self, step: Step<S, A>) {
+        self.steps.push_back(step);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Step<S, A>> {
+        let step = self.steps.pop_front();
+        if step.is_some() {
+            self.start += 1;
+        }
+        step
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.start = 0;
+    }
+}
+
+/// (S, A, b(A|S)) right after the window, bootstrapped from unless the episode terminated
+pub type Bootstrap<S, A> = (S, A, f32);
+
+/// n-step methods sharing the episode loop: once n steps are in the history, the oldest one is
+/// updated towards `n_step_target` and dropped, the rest are flushed at the end of the episode.
+/// Actions are always chosen epsilon-greedily with the config's epsilon
+pub trait NStepLearner<E: Environment>: TabularLearner<E> {
+    fn history(&self) -> &NStepHistory<E::Observation, E::Action>;
+    fn history_mut(&mut self) -> &mut NStepHistory<E::Observation, E::Action>;
+
+    /// Target for the oldest step in the history, and the importance sampling ratio that scales
+    /// its step size (1 for methods that don't need one)
+    fn n_step_target(
+        &self,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        env: &E,
+    ) -> Result<(Reward, f32)>;
+
+    fn update_oldest(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        env: &E,
+    ) -> Result<()> {
+        let (target, ratio) = self.n_step_target(bootstrap, env)?;
+        if let Some(oldest) = self.history_mut().pop_front() {
+            let alpha = self.config().alpha * ratio;
+            self.observed_update(observer, alpha, oldest.state, oldest.action, target);
+        }
+        Ok(())
+    }
+
+    // env is preinitialized
+    fn n_step_episode(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.data_mut().terminal_state = env.get_terminal();
+        self.history_mut().clear(); // in case the previous episode failed midway
+        let epsilon = self.config().epsilon;
+        let mut state = env.current_observation();
+        let mut action = self.epsilon_greedy(epsilon, state, env)?;
+        let mut probability = self.epsilon_greedy_probability(epsilon, state, action, env)?;
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config().gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+            self.history_mut().push(Step {
+                state,
+                action,
+                reward,
+                probability,
+            });
+
+            if terminal {
+                break;
+            }
+
+            let next_action = self.epsilon_greedy(epsilon, next_state, env)?;
+            let next_probability =
+                self.epsilon_greedy_probability(epsilon, next_state, next_action, env)?;
+
+            let bootstrap = Some((next_state, next_action, next_probability));
+
+            if stats.truncate(self.config().max_steps) {
+                // the episode goes on past the cut, so every remaining step bootstraps
+                while !self.history().is_empty() {
+                    self.update_oldest(observer, bootstrap, env)?;
+                }
+                break;
+            }
+
+            if self.history().is_full() {
+                self.update_oldest(observer, bootstrap, env)?;
+            }
+
+            state = next_state;
+            action = next_action;
+            probability = next_probability;
+        }
+
+        while !self.history().is_empty() {
+            self.update_oldest(observer, None, env)?;
+        }
+        self.history_mut().clear();
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+}
+
+/// Checks the number of steps given to an n-step learner's constructor
+pub fn validate_n(n: usize) -> Result<()> {
+    if n == 0 {
+        return Err(Error::InvalidConfig(
+            "n-step learners need n of at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the arguments shared by the off-policy n-step learners' constructors
+pub fn validate(n: usize, target_epsilon: f32) -> Result<()> {
+    validate_n(n)?;
+    if !(0. ..=1.).contains(&target_epsilon) {
+        return Err(Error::InvalidConfig(format!(
+            "target epsilon {} is not a probability",
+            target_epsilon
+        )));
+    }
+    Ok(())
+}
+
+/// `learn_batch` of the n-step learners, which need consecutive steps of one episode
+pub fn reject_batch() -> Result<()> {
+    Err(Error::InvalidConfig(
+        "n-step learners can't learn from batched transitions, use replay".to_string(),
+    ))
+}
+
+/// Per-decision return of Q(sigma) (pg. 160 of <book>) for the oldest step in the history,
+/// under an epsilon-greedy target policy. sigma(t) = 1 samples like SARSA, 0 backs up the
+/// expectation like Tree Backup
+pub fn sigma_return<E: Environment, L: NStepLearner<E> + ?Sized>(
+    learner: &L,
+    target_epsilon: f32,
+    sigma: &dyn Fn(usize) -> f32,
+    bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+    env: &E,
+) -> Result<Reward> {
+    let history = learner.history();
+    let m = history.len();
+    let gamma = learner.config().gamma;
+    let mut g = history.get(m - 1).reward;
+
+    let (mut state, mut action, mut probability) = match bootstrap {
+        Some((state, action, probability)) => {
+            g = learner.data().value(learner.config(), state, action);
+            (state, action, probability)
+        }
+        None => {
+            // R_T is the return of the last step, continue backwards from it
+            let last = history.get(m - 1);
+            (last.state, last.action, last.probability)
+        }
+    };
+    let mut i = if bootstrap.is_some() { m } else { m - 1 };
+
+    while i >= 1 {
+        let reward = history.get(i - 1).reward;
+        let pi = learner.epsilon_greedy_probability(target_epsilon, state, action, env)?;
+        let rho = pi / probability;
+        let s = sigma(history.start() + i);
+        let q = learner.data().value(learner.config(), state, action);
+        let expected = learner.epsilon_greedy_value(target_epsilon, state, env)?;
+        g = reward + gamma * (s * rho + (1. - s) * pi) * (g - q) + gamma * expected;
+
+        i -= 1;
+        let previous = history.get(i);
+        state = previous.state;
+        action = previous.action;
+        probability = previous.probability;
+    }
+
+    Ok(g)
+}
@@ -1,93 +1,112 @@
-use std::collections::VecDeque;
-
-use crate::environment::{Environment, Reward};
-pub use crate::learner::{TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::checkpoint::{Checkpointable, TabularState};
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::learner::n_step::{reject_batch, validate_n};
+pub use crate::learner::n_step::{Bootstrap, NStepHistory, NStepLearner};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::Result;
 
 pub struct NStepSarsa<E: Environment> {
     pub config: TabularLearnerConfig,
     data: TabularLearnerData<E>,
-    n: usize,                                         // steps
-    history: VecDeque<(E::State, E::Action, Reward)>, // last n triplets (S_t, A_t, R_{t+1})
+    history: NStepHistory<E::Observation, E::Action>, // last n steps (S_t, A_t, R_{t+1})
 }
 
 impl<E: Environment> NStepSarsa<E> {
-    pub fn new(n: usize, config: TabularLearnerConfig, terminal_state: E::State) -> NStepSarsa<E> {
+    pub fn new(
+        n: usize,
+        config: TabularLearnerConfig,
+        terminal_state: Option<E::Observation>,
+    ) -> Result<NStepSarsa<E>> {
+        validate_n(n)?;
+
         let data = TabularLearnerData::new(terminal_state);
-        NStepSarsa {
+        Ok(NStepSarsa {
             config,
             data,
-            n,
-            history: VecDeque::with_capacity(n),
-        }
+            history: NStepHistory::new(n),
+        })
     }
 }
 
-impl<E: Environment> TabularLearner<E> for NStepSarsa<E> {
-    // env is preinitialized
-    fn episode(&mut self, env: &mut E) -> Reward {
-        self.data.terminal_state = env.get_terminal();
-        let mut action = self.epsilon_greedy(self.config.epsilon, env.current_state(), env);
-        let mut state = env.current_state();
-        let mut gain: Reward = 0.0;
-
-        while let Some((next_state, reward)) = env.take_action(action) {
-            // episode() assumes gamma=1
-            gain += reward;
-            let next_action = self.epsilon_greedy(self.config.epsilon, next_state, env);
-            if self.config.debug {
-                println!(
-                    "S: {:?}, A: {:?}, R: {}, S': {:?}, A': {:?}",
-                    state, action, reward, next_state, next_action
-                );
-            }
-
-            if self.history.len() == self.n {
-                let mut target = self.history[self.n - 1].2;
-
-                for i in (0..self.n - 1).rev() {
-                    target = self.history[i].2 + self.config.gamma * target;
-                }
+impl<E: Environment> NStepLearner<E> for NStepSarsa<E> {
+    fn history(&self) -> &NStepHistory<E::Observation, E::Action> {
+        &self.history
+    }
 
-                target += self.config.gamma.powf(self.n as f32)
-                    * self.data.value(&self.config, state, action);
+    fn history_mut(&mut self) -> &mut NStepHistory<E::Observation, E::Action> {
+        &mut self.history
+    }
 
-                self.update(
-                    self.config.alpha,
-                    self.history[0].0,
-                    self.history[0].1,
-                    target,
-                );
+    fn n_step_target(
+        &self,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        _env: &E,
+    ) -> Result<(Reward, f32)> {
+        let n = self.history.len(); // fewer than n steps left at the end of an episode
+        let mut target = match bootstrap {
+            Some((state, action, _)) => self.data.value(&self.config, state, action),
+            None => 0.,
+        };
+
+        for i in (0..n).rev() {
+            target = self.history.get(i).reward + self.config.gamma * target;
+        }
 
-                self.history.pop_front();
-            }
+        Ok((target, 1.))
+    }
+}
 
-            self.history.push_back((state, action, reward));
+impl<E: Environment> TabularLearner<E> for NStepSarsa<E> {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.n_step_episode(env, observer)
+    }
 
-            state = next_state;
-            action = next_action;
-        }
+    // n-step returns computed directly from the recorded trajectory
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+        let len = trajectory.len();
+        let terminated = trajectory.last().is_some_and(|t| t.terminal);
 
-        assert!(self.history.len() <= self.n);
-        let n = self.history.len(); // to handle cases where n_states < self.n
+        for tau in 0..len {
+            let end = tau + self.history.n();
+            if end >= len && !terminated {
+                break; // can't bootstrap without A_{tau+n}
+            }
 
-        for i in 0..n {
-            let mut target = self.history[n - 1].2;
+            let mut target = 0.;
+            for (i, transition) in trajectory[tau..end.min(len)].iter().enumerate() {
+                target += self.config.gamma.powi(i as i32) * transition.reward;
+            }
 
-            for j in (i..n - 1).rev() {
-                target = self.history[j].2 + self.config.gamma * target;
+            if end < len {
+                target += self.config.gamma.powf(self.history.n() as f32)
+                    * self
+                        .data
+                        .value(&self.config, trajectory[end].state, trajectory[end].action);
             }
 
             self.update(
                 self.config.alpha,
-                self.history[i].0,
-                self.history[i].1,
+                trajectory[tau].state,
+                trajectory[tau].action,
                 target,
             );
         }
 
-        self.history.clear();
+        Ok(())
+    }
 
-        gain
+    fn learn_batch(&mut self, _batch: &[EnvTransition<E>], _env: &E) -> Result<()> {
+        reject_batch()
     }
 
     fn data(&self) -> &TabularLearnerData<E> {
@@ -106,3 +125,29 @@ impl<E: Environment> TabularLearner<E> for NStepSarsa<E> {
         &mut self.config
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NStepSarsaState<S, A> {
+    pub tabular: TabularState<S, A>,
+    pub history: NStepHistory<S, A>,
+}
+
+impl<E: Environment> Checkpointable for NStepSarsa<E>
+where
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+{
+    type State = NStepSarsaState<E::Observation, E::Action>;
+
+    fn save(&self) -> Self::State {
+        NStepSarsaState {
+            tabular: TabularState::save(&self.config, &self.data),
+            history: self.history.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        state.tabular.restore(&mut self.config, &mut self.data);
+        self.history = state.history;
+    }
+}
@@ -0,0 +1,50 @@
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::learner::EpisodeStats;
+
+/// Q(state, action) was moved towards target, ending up at value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueUpdate<S, A> {
+    pub state: S,
+    pub action: A,
+    pub target: Reward,
+    pub value: Reward,
+}
+
+/// Receives step events from learners during an episode
+pub trait StepObserver<E: Environment> {
+    /// Called after every environment step
+    fn on_step(&mut self, _transition: &EnvTransition<E>) {}
+
+    /// Called after every value update, including planning and end-of-episode updates
+    fn on_update(&mut self, _update: &ValueUpdate<E::Observation, E::Action>) {}
+
+    fn on_episode_end(&mut self, _stats: &EpisodeStats) {}
+}
+
+/// Ignores all events
+pub struct NoObserver;
+
+impl<E: Environment> StepObserver<E> for NoObserver {}
+
+/// Prints steps and updates to stdout
+pub struct Printer {
+    pub updates: bool, // also print value updates
+}
+
+impl<E: Environment> StepObserver<E> for Printer {
+    fn on_step(&mut self, transition: &EnvTransition<E>) {
+        println!(
+            "S: {:?}, A: {:?}, R: {}, S': {:?}",
+            transition.state, transition.action, transition.reward, transition.next_state
+        );
+    }
+
+    fn on_update(&mut self, update: &ValueUpdate<E::Observation, E::Action>) {
+        if self.updates {
+            println!(
+                "Q({:?}, {:?}) <- {} (target {})",
+                update.state, update.action, update.value, update.target
+            );
+        }
+    }
+}
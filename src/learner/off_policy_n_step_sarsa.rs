@@ -0,0 +1,111 @@
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::learner::n_step::{reject_batch, validate};
+pub use crate::learner::n_step::{Bootstrap, NStepHistory, NStepLearner};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::Result;
+
+/// n-step SARSA learning an epsilon-greedy target policy with target_epsilon (0 for greedy)
+/// from the behaviour policy, weighting each update by the importance sampling ratio
+pub struct OffPolicyNStepSarsa<E: Environment> {
+    pub config: TabularLearnerConfig,
+    pub target_epsilon: f32,
+    data: TabularLearnerData<E>,
+    history: NStepHistory<E::Observation, E::Action>,
+}
+
+impl<E: Environment> OffPolicyNStepSarsa<E> {
+    pub fn new(
+        n: usize,
+        config: TabularLearnerConfig,
+        target_epsilon: f32,
+        terminal_state: Option<E::Observation>,
+    ) -> Result<OffPolicyNStepSarsa<E>> {
+        validate(n, target_epsilon)?;
+
+        Ok(OffPolicyNStepSarsa {
+            config,
+            target_epsilon,
+            data: TabularLearnerData::new(terminal_state),
+            history: NStepHistory::new(n),
+        })
+    }
+
+    fn ratio(
+        &self,
+        state: E::Observation,
+        action: E::Action,
+        probability: f32,
+        env: &E,
+    ) -> Result<f32> {
+        let pi = self.epsilon_greedy_probability(self.target_epsilon, state, action, env)?;
+        Ok(pi / probability)
+    }
+}
+
+impl<E: Environment> NStepLearner<E> for OffPolicyNStepSarsa<E> {
+    fn history(&self) -> &NStepHistory<E::Observation, E::Action> {
+        &self.history
+    }
+
+    fn history_mut(&mut self) -> &mut NStepHistory<E::Observation, E::Action> {
+        &mut self.history
+    }
+
+    // ratio over A_{tau+1}..A_{tau+n}, A_tau is already given
+    fn n_step_target(
+        &self,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        env: &E,
+    ) -> Result<(Reward, f32)> {
+        let n = self.history.len();
+        let (mut target, mut ratio) = match bootstrap {
+            Some((state, action, probability)) => (
+                self.data.value(&self.config, state, action),
+                self.ratio(state, action, probability, env)?,
+            ),
+            None => (0., 1.),
+        };
+
+        for i in (0..n).rev() {
+            let step = self.history.get(i);
+            target = step.reward + self.config.gamma * target;
+            if i > 0 {
+                ratio *= self.ratio(step.state, step.action, step.probability, env)?;
+            }
+        }
+
+        Ok((target, ratio))
+    }
+}
+
+impl<E: Environment> TabularLearner<E> for OffPolicyNStepSarsa<E> {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.n_step_episode(env, observer)
+    }
+
+    fn learn_batch(&mut self, _batch: &[EnvTransition<E>], _env: &E) -> Result<()> {
+        reject_batch()
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        &mut self.data
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
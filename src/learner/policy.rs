@@ -0,0 +1,164 @@
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::environment::{Environment, Reward};
+use crate::learner::{EpisodeStats, NoObserver, StepObserver, ValueUpdate};
+use crate::{random, Error, Result};
+
+#[derive(Clone)]
+pub struct PolicyLearnerConfig {
+    pub alpha: f32,               // policy (actor) step size
+    pub beta: f32,                // baseline/critic step size
+    pub gamma: f32,               // discount factor
+    pub max_steps: Option<usize>, // episodes longer than this are truncated
+}
+
+impl PolicyLearnerConfig {
+    pub fn new(alpha: f32, beta: f32, gamma: f32) -> PolicyLearnerConfig {
+        PolicyLearnerConfig {
+            alpha,
+            beta,
+            gamma,
+            max_steps: None,
+        }
+    }
+}
+
+/// Softmax over tabular action preferences h(s, a)
+pub struct SoftmaxPolicy<E: Environment> {
+    pub preferences: HashMap<(E::Observation, E::Action), f32>,
+}
+
+impl<E: Environment> SoftmaxPolicy<E> {
+    pub fn new() -> SoftmaxPolicy<E> {
+        SoftmaxPolicy {
+            preferences: HashMap::new(),
+        }
+    }
+
+    pub fn preference(&self, state: E::Observation, action: E::Action) -> f32 {
+        *self.preferences.get(&(state, action)).unwrap_or(&0.)
+    }
+
+    /// pi(a|state) for every available action
+    pub fn probabilities(&self, state: E::Observation, env: &E) -> Result<Vec<(E::Action, f32)>> {
+        let available = env.available_actions(state);
+        if available.is_empty() {
+            return Err(Error::NoAvailableActions(format!("{:?}", state)));
+        }
+
+        let preferences: Vec<f32> = available
+            .iter()
+            .map(|action| self.preference(state, *action))
+            .collect();
+        let max = preferences
+            .iter()
+            .cloned()
+            .fold(f32::NEG_INFINITY, f32::max);
+        if max.is_nan() || preferences.iter().any(|p| p.is_nan()) {
+            return Err(Error::InvalidValue(format!("{:?}", state)));
+        }
+
+        // shifted by the max for numerical stability
+        let exps: Vec<f32> = preferences.iter().map(|p| (p - max).exp()).collect();
+        let total: f32 = exps.iter().sum();
+
+        Ok(available
+            .into_iter()
+            .zip(exps)
+            .map(|(action, e)| (action, e / total))
+            .collect())
+    }
+
+    pub fn sample(&self, state: E::Observation, env: &E) -> Result<E::Action> {
+        let probabilities = self.probabilities(state, env)?;
+        let mut remaining = random::rng().gen::<f32>();
+
+        for (action, probability) in probabilities.iter() {
+            remaining -= probability;
+            if remaining < 0. {
+                return Ok(*action);
+            }
+        }
+
+        Ok(probabilities[probabilities.len() - 1].0) // rounding
+    }
+
+    /// Gradient step on ln pi(action|state): h(s, b) += step * (1[b = action] - pi(b|s))
+    pub fn step(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        step: f32,
+        target: Reward,
+        state: E::Observation,
+        action: E::Action,
+        env: &E,
+    ) -> Result<()> {
+        for (b, probability) in self.probabilities(state, env)? {
+            let indicator = if b == action { 1. } else { 0. };
+            let value = self.preference(state, b) + step * (indicator - probability);
+            self.preferences.insert((state, b), value);
+
+            if b == action {
+                observer.on_update(&ValueUpdate {
+                    state,
+                    action,
+                    target,
+                    value,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: Environment> Default for SoftmaxPolicy<E> {
+    fn default() -> SoftmaxPolicy<E> {
+        SoftmaxPolicy::new()
+    }
+}
+
+/// Tabular state values v(s), zero at the terminal state
+pub struct StateValues<E: Environment> {
+    pub v: HashMap<E::Observation, Reward>,
+    pub terminal_state: Option<E::Observation>, // None for continuing tasks
+}
+
+impl<E: Environment> StateValues<E> {
+    pub fn new(terminal_state: Option<E::Observation>) -> StateValues<E> {
+        StateValues {
+            v: HashMap::new(),
+            terminal_state,
+        }
+    }
+
+    pub fn value(&self, state: E::Observation) -> Reward {
+        if Some(state) == self.terminal_state {
+            0.
+        } else {
+            *self.v.get(&state).unwrap_or(&0.)
+        }
+    }
+
+    pub fn update(&mut self, beta: f32, state: E::Observation, target: Reward) {
+        let value = self.value(state);
+        self.v.insert(state, value + beta * (target - value));
+    }
+}
+
+/// Learners with an explicit stochastic policy, counterpart of `TabularLearner`
+pub trait PolicyLearner<E: Environment> {
+    /// Runs an episode, reporting steps and preference updates to the observer
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats>;
+    fn episode(&mut self, env: &mut E) -> Result<EpisodeStats> {
+        self.episode_with(env, &mut NoObserver)
+    }
+    fn policy(&self) -> &SoftmaxPolicy<E>;
+    fn config(&self) -> &PolicyLearnerConfig;
+    fn config_mut(&mut self) -> &mut PolicyLearnerConfig;
+}
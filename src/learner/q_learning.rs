@@ -1,5 +1,12 @@
-use crate::environment::{Environment, Reward};
-pub use crate::learner::{TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::checkpoint::{Checkpointable, TabularState};
+use crate::environment::{Environment, Transition};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::Result;
 
 pub struct QLearning<E: Environment> {
     pub config: TabularLearnerConfig,
@@ -7,7 +14,10 @@ pub struct QLearning<E: Environment> {
 }
 
 impl<E: Environment> QLearning<E> {
-    pub fn new(config: TabularLearnerConfig, terminal_state: E::State) -> QLearning<E> {
+    pub fn new(
+        config: TabularLearnerConfig,
+        terminal_state: Option<E::Observation>,
+    ) -> QLearning<E> {
         let data = TabularLearnerData::new(terminal_state);
         QLearning { config, data }
     }
@@ -15,30 +25,39 @@ impl<E: Environment> QLearning<E> {
 
 impl<E: Environment> TabularLearner<E> for QLearning<E> {
     // env is preinitialized
-    fn episode(&mut self, env: &mut E) -> Reward {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
         self.data.terminal_state = env.get_terminal();
-        let mut state = env.current_state();
-        let mut gain: Reward = 0.0;
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
 
         loop {
-            let action = self.epsilon_greedy(self.config.epsilon, env.current_state(), env);
-            let (next_state, reward) = env.take_action(action).unwrap();
-            // episode() assumes gamma=1
-            gain += reward;
-            let target = reward + self.config.gamma * self.max_action_value(next_state, &env);
-            self.update(self.config.alpha, state, action, target);
-
-            if self.config.debug {
-                println!("{:?} -> {:?}", state, next_state);
-            }
+            let action =
+                self.epsilon_greedy(self.config.epsilon, env.current_observation(), env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let transition = Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal: env.is_terminal(next_state),
+            };
+            stats.record(reward, self.config.gamma, transition.terminal);
+            observer.on_step(&transition);
+            let target = self.q_target(&transition, env)?;
+            self.observed_update(observer, self.config.alpha, state, action, target);
 
             state = next_state;
-            if state == self.data.terminal_state {
+            if Some(state) == self.data.terminal_state || stats.truncate(self.config.max_steps) {
                 break;
             }
         }
 
-        gain
+        observer.on_episode_end(&stats);
+        Ok(stats)
     }
 
     fn data(&self) -> &TabularLearnerData<E> {
@@ -57,3 +76,19 @@ impl<E: Environment> TabularLearner<E> for QLearning<E> {
         &mut self.config
     }
 }
+
+impl<E: Environment> Checkpointable for QLearning<E>
+where
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+{
+    type State = TabularState<E::Observation, E::Action>;
+
+    fn save(&self) -> Self::State {
+        TabularState::save(&self.config, &self.data)
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        state.restore(&mut self.config, &mut self.data);
+    }
+}
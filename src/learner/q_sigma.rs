@@ -0,0 +1,130 @@
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::learner::n_step::{reject_batch, validate};
+pub use crate::learner::n_step::{sigma_return, Bootstrap, NStepHistory, NStepLearner};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::{Error, Result};
+
+/// Degree of sampling sigma at each step, between 0 (Tree Backup) and 1 (SARSA)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SigmaSchedule {
+    Constant(f32),
+    /// 1 on even time steps, 0 on odd ones
+    Alternating,
+    /// rate^episode, starting out as SARSA and moving towards Tree Backup
+    Decaying {
+        rate: f32,
+    },
+}
+
+impl SigmaSchedule {
+    pub fn sigma(self, t: usize, episode: usize) -> f32 {
+        match self {
+            SigmaSchedule::Constant(sigma) => sigma,
+            SigmaSchedule::Alternating => {
+                if t % 2 == 0 {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            SigmaSchedule::Decaying { rate } => rate.powi(episode as i32),
+        }
+    }
+}
+
+/// Off-policy n-step Q(sigma). The target policy is epsilon-greedy with target_epsilon
+/// (0 for greedy), the behaviour policy uses the config's epsilon
+pub struct QSigma<E: Environment> {
+    pub config: TabularLearnerConfig,
+    pub target_epsilon: f32,
+    pub schedule: SigmaSchedule,
+    data: TabularLearnerData<E>,
+    history: NStepHistory<E::Observation, E::Action>,
+    episodes: usize, // completed, for decaying schedules
+}
+
+impl<E: Environment> QSigma<E> {
+    pub fn new(
+        n: usize,
+        config: TabularLearnerConfig,
+        target_epsilon: f32,
+        schedule: SigmaSchedule,
+        terminal_state: Option<E::Observation>,
+    ) -> Result<QSigma<E>> {
+        validate(n, target_epsilon)?;
+        let probability = match schedule {
+            SigmaSchedule::Constant(sigma) => sigma,
+            SigmaSchedule::Alternating => 0.,
+            SigmaSchedule::Decaying { rate } => rate,
+        };
+        if !(0. ..=1.).contains(&probability) {
+            return Err(Error::InvalidConfig(format!(
+                "sigma schedule {:?} doesn't give probabilities",
+                schedule
+            )));
+        }
+
+        Ok(QSigma {
+            config,
+            target_epsilon,
+            schedule,
+            data: TabularLearnerData::new(terminal_state),
+            history: NStepHistory::new(n),
+            episodes: 0,
+        })
+    }
+}
+
+impl<E: Environment> NStepLearner<E> for QSigma<E> {
+    fn history(&self) -> &NStepHistory<E::Observation, E::Action> {
+        &self.history
+    }
+
+    fn history_mut(&mut self) -> &mut NStepHistory<E::Observation, E::Action> {
+        &mut self.history
+    }
+
+    fn n_step_target(
+        &self,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        env: &E,
+    ) -> Result<(Reward, f32)> {
+        let sigma = |t| self.schedule.sigma(t, self.episodes);
+        let target = sigma_return(self, self.target_epsilon, &sigma, bootstrap, env)?;
+        Ok((target, 1.))
+    }
+}
+
+impl<E: Environment> TabularLearner<E> for QSigma<E> {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        let stats = self.n_step_episode(env, observer)?;
+        self.episodes += 1;
+        Ok(stats)
+    }
+
+    fn learn_batch(&mut self, _batch: &[EnvTransition<E>], _env: &E) -> Result<()> {
+        reject_batch()
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        &mut self.data
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
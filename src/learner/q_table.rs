@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::environment::{Indexing, Reward};
+
+enum Storage<S, A> {
+    Sparse(HashMap<(S, A), Reward>),
+    Dense {
+        indexing: Arc<dyn Indexing<S, A>>,
+        values: Vec<Option<Reward>>, // state-major, None until first set
+        len: usize,                  // number of set values
+    },
+}
+
+/// Action values, by default in a hash map. `QTable::dense` stores them in a flat array
+/// instead, which is much faster when the environment can number its observations
+pub struct QTable<S, A> {
+    storage: Storage<S, A>,
+}
+
+impl<S: Copy + Hash + Eq, A: Copy + Hash + Eq> QTable<S, A> {
+    pub fn new() -> QTable<S, A> {
+        QTable {
+            storage: Storage::Sparse(HashMap::new()),
+        }
+    }
+
+    /// Replace a learner's table with e.g.
+    /// `learner.data_mut().q = QTable::dense(env.indexing())`
+    pub fn dense<I: Indexing<S, A> + 'static>(indexing: I) -> QTable<S, A> {
+        let size = indexing.states() * indexing.actions();
+        QTable {
+            storage: Storage::Dense {
+                indexing: Arc::new(indexing),
+                values: vec![None; size],
+                len: 0,
+            },
+        }
+    }
+
+    pub fn is_dense(&self) -> bool {
+        matches!(self.storage, Storage::Dense { .. })
+    }
+
+    pub fn get(&self, key: &(S, A)) -> Option<&Reward> {
+        match &self.storage {
+            Storage::Sparse(q) => q.get(key),
+            Storage::Dense {
+                indexing, values, ..
+            } => values[index(indexing.as_ref(), key)].as_ref(),
+        }
+    }
+
+    /// Returns the previous value, if any
+    pub fn insert(&mut self, key: (S, A), value: Reward) -> Option<Reward> {
+        match &mut self.storage {
+            Storage::Sparse(q) => q.insert(key, value),
+            Storage::Dense {
+                indexing,
+                values,
+                len,
+            } => {
+                let previous = values[index(indexing.as_ref(), &key)].replace(value);
+                if previous.is_none() {
+                    *len += 1;
+                }
+                previous
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Sparse(q) => q.len(),
+            Storage::Dense { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all values, keeping the storage kind
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Sparse(q) => q.clear(),
+            Storage::Dense { values, len, .. } => {
+                values.iter_mut().for_each(|value| *value = None);
+                *len = 0;
+            }
+        }
+    }
+
+    /// Set values in no particular order
+    pub fn iter(&self) -> Box<dyn Iterator<Item = ((S, A), Reward)> + '_> {
+        match &self.storage {
+            Storage::Sparse(q) => Box::new(q.iter().map(|(key, value)| (*key, *value))),
+            Storage::Dense {
+                indexing, values, ..
+            } => {
+                let actions = indexing.actions();
+                Box::new(values.iter().enumerate().filter_map(move |(i, value)| {
+                    value.map(|value| {
+                        let key = (indexing.state(i / actions), indexing.action(i % actions));
+                        (key, value)
+                    })
+                }))
+            }
+        }
+    }
+}
+
+fn index<S: Copy, A: Copy>(indexing: &dyn Indexing<S, A>, (state, action): &(S, A)) -> usize {
+    indexing.state_index(*state) * indexing.actions() + indexing.action_index(*action)
+}
+
+impl<S: Copy + Hash + Eq, A: Copy + Hash + Eq> Default for QTable<S, A> {
+    fn default() -> QTable<S, A> {
+        QTable::new()
+    }
+}
+
+impl<S: Clone, A: Clone> Clone for QTable<S, A> {
+    fn clone(&self) -> QTable<S, A> {
+        let storage = match &self.storage {
+            Storage::Sparse(q) => Storage::Sparse(q.clone()),
+            Storage::Dense {
+                indexing,
+                values,
+                len,
+            } => Storage::Dense {
+                indexing: Arc::clone(indexing),
+                values: values.clone(),
+                len: *len,
+            },
+        };
+        QTable { storage }
+    }
+}
+
+impl<S: Copy + Hash + Eq, A: Copy + Hash + Eq> std::iter::FromIterator<((S, A), Reward)>
+    for QTable<S, A>
+{
+    fn from_iter<T: IntoIterator<Item = ((S, A), Reward)>>(iter: T) -> QTable<S, A> {
+        QTable {
+            storage: Storage::Sparse(iter.into_iter().collect()),
+        }
+    }
+}
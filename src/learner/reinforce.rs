@@ -0,0 +1,117 @@
+use crate::environment::{Environment, Reward, Transition};
+pub use crate::learner::policy::{PolicyLearner, PolicyLearnerConfig, SoftmaxPolicy, StateValues};
+use crate::learner::{EpisodeStats, StepObserver};
+use crate::Result;
+
+/// Monte Carlo policy gradient, optionally with a learned state-value baseline
+pub struct Reinforce<E: Environment> {
+    pub config: PolicyLearnerConfig,
+    policy: SoftmaxPolicy<E>,
+    baseline: Option<StateValues<E>>,
+}
+
+impl<E: Environment> Reinforce<E> {
+    pub fn new(
+        config: PolicyLearnerConfig,
+        baseline: bool,
+        terminal_state: Option<E::Observation>,
+    ) -> Reinforce<E> {
+        Reinforce {
+            config,
+            policy: SoftmaxPolicy::new(),
+            baseline: if baseline {
+                Some(StateValues::new(terminal_state))
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn baseline(&self) -> Option<&StateValues<E>> {
+        self.baseline.as_ref()
+    }
+}
+
+impl<E: Environment> PolicyLearner<E> for Reinforce<E> {
+    // env is preinitialized
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        if let Some(baseline) = &mut self.baseline {
+            baseline.terminal_state = env.get_terminal();
+        }
+        let mut episode: Vec<(E::Observation, E::Action, Reward)> = Vec::new(); // (S_t, A_t, R_{t+1})
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let action = self.policy.sample(state, env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config.gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+            episode.push((state, action, reward));
+
+            state = next_state;
+            if terminal || stats.truncate(self.config.max_steps) {
+                break;
+            }
+        }
+
+        let mut returns = vec![0.; episode.len()];
+        // the rest of a truncated episode is estimated by the baseline, if there is one
+        let mut g = match self.baseline.as_ref() {
+            Some(baseline) if stats.truncated => baseline.value(state),
+            _ => 0.,
+        };
+        for (t, (_, _, reward)) in episode.iter().enumerate().rev() {
+            g = reward + self.config.gamma * g;
+            returns[t] = g;
+        }
+
+        let mut discount = 1.; // gamma^t
+        for ((state, action, _), g) in episode.into_iter().zip(returns) {
+            let delta = match self.baseline.as_mut() {
+                Some(baseline) => {
+                    let delta = g - baseline.value(state);
+                    baseline.update(self.config.beta, state, g);
+                    delta
+                }
+                None => g,
+            };
+
+            self.policy.step(
+                observer,
+                self.config.alpha * discount * delta,
+                g,
+                state,
+                action,
+                env,
+            )?;
+            discount *= self.config.gamma;
+        }
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn policy(&self) -> &SoftmaxPolicy<E> {
+        &self.policy
+    }
+
+    fn config(&self) -> &PolicyLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut PolicyLearnerConfig {
+        &mut self.config
+    }
+}
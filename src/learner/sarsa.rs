@@ -1,5 +1,12 @@
-use crate::environment::{Environment, Reward};
-pub use crate::learner::{TabularLearner, TabularLearnerConfig, TabularLearnerData};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::checkpoint::{Checkpointable, TabularState};
+use crate::environment::{EnvTransition, Environment, Transition};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::Result;
 
 pub struct Sarsa<E: Environment> {
     pub config: TabularLearnerConfig,
@@ -7,7 +14,7 @@ pub struct Sarsa<E: Environment> {
 }
 
 impl<E: Environment> Sarsa<E> {
-    pub fn new(config: TabularLearnerConfig, terminal_state: E::State) -> Sarsa<E> {
+    pub fn new(config: TabularLearnerConfig, terminal_state: Option<E::Observation>) -> Sarsa<E> {
         let data = TabularLearnerData::new(terminal_state);
         Sarsa { config, data }
     }
@@ -15,30 +22,92 @@ impl<E: Environment> Sarsa<E> {
 
 impl<E: Environment> TabularLearner<E> for Sarsa<E> {
     // env is preinitialized
-    fn episode(&mut self, env: &mut E) -> Reward {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
         self.data.terminal_state = env.get_terminal();
-        let mut action = self.epsilon_greedy(self.config.epsilon, env.current_state(), env);
-        let mut state = env.current_state();
-        let mut gain: Reward = 0.0;
-
-        while let Some((next_state, reward)) = env.take_action(action) {
-            // episode() assumes gamma=1
-            gain += reward;
-            let next_action = self.epsilon_greedy(self.config.epsilon, next_state, env);
-            // if self.config.debug {
-            //     println!(
-            //         "S: {:?}, A: {:?}, R: {}, S': {:?}, A': {:?}",
-            //         state, action, reward, next_state, next_action
-            //     );
-            // }
+        let mut action =
+            self.epsilon_greedy(self.config.epsilon, env.current_observation(), env)?;
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config.gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+
+            if terminal {
+                self.observed_update(observer, self.config.alpha, state, action, reward);
+                break;
+            }
+
+            let next_action = self.epsilon_greedy(self.config.epsilon, next_state, env)?;
             let target =
                 reward + self.config.gamma * self.data.value(&self.config, next_state, next_action);
-            self.update(self.config.alpha, state, action, target);
+            self.observed_update(observer, self.config.alpha, state, action, target);
+            if stats.truncate(self.config.max_steps) {
+                break;
+            }
             state = next_state;
             action = next_action;
         }
 
-        gain
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    // uses the recorded A_{t+1} instead of the greedy action
+    fn replay(&mut self, trajectory: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for (i, transition) in trajectory.iter().enumerate() {
+            let next_value = match trajectory.get(i + 1) {
+                _ if transition.terminal => 0.,
+                Some(next) => self.data.value(&self.config, next.state, next.action),
+                None => break, // A_{t+1} was not recorded
+            };
+            let target = transition.reward + self.config.gamma * next_value;
+            self.update(
+                self.config.alpha,
+                transition.state,
+                transition.action,
+                target,
+            );
+        }
+
+        Ok(())
+    }
+
+    // A_{t+1} isn't known for batched transitions, so the expectation under the learner's own
+    // epsilon-greedy policy stands in for it (expected SARSA)
+    fn learn_batch(&mut self, batch: &[EnvTransition<E>], env: &E) -> Result<()> {
+        self.data.terminal_state = env.get_terminal();
+
+        for transition in batch {
+            let next_value = if transition.terminal {
+                0.
+            } else {
+                self.epsilon_greedy_value(self.config.epsilon, transition.next_state, env)?
+            };
+            let target = transition.reward + self.config.gamma * next_value;
+            self.update(
+                self.config.alpha,
+                transition.state,
+                transition.action,
+                target,
+            );
+        }
+
+        Ok(())
     }
 
     fn data(&self) -> &TabularLearnerData<E> {
@@ -57,3 +126,19 @@ impl<E: Environment> TabularLearner<E> for Sarsa<E> {
         &mut self.config
     }
 }
+
+impl<E: Environment> Checkpointable for Sarsa<E>
+where
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+{
+    type State = TabularState<E::Observation, E::Action>;
+
+    fn save(&self) -> Self::State {
+        TabularState::save(&self.config, &self.data)
+    }
+
+    fn restore(&mut self, state: Self::State) {
+        state.restore(&mut self.config, &mut self.data);
+    }
+}
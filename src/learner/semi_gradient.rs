@@ -0,0 +1,244 @@
+use rand::Rng;
+
+use crate::approximation::{Features, LinearQ};
+use crate::environment::{Environment, Reward, Transition};
+use crate::learner::{
+    EpisodeStats, NoObserver, StepObserver, TabularLearnerConfig, TieBreaking, ValueUpdate,
+};
+use crate::{random, Error, Result};
+
+/// Learners over a linear action-value function, counterpart of `TabularLearner`.
+/// Only alpha, epsilon, gamma, tie_breaking and max_steps of the config are used
+pub trait ApproximateLearner<E: Environment> {
+    type Features: Features<E::Observation>;
+
+    /// Runs an episode, reporting steps and value updates to the observer
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats>;
+    fn episode(&mut self, env: &mut E) -> Result<EpisodeStats> {
+        self.episode_with(env, &mut NoObserver)
+    }
+    fn q(&self) -> &LinearQ<E, Self::Features>;
+    fn q_mut(&mut self) -> &mut LinearQ<E, Self::Features>;
+    fn config(&self) -> &TabularLearnerConfig;
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig;
+
+    fn observed_update(
+        &mut self,
+        observer: &mut dyn StepObserver<E>,
+        alpha: f32,
+        state: E::Observation,
+        action: E::Action,
+        target: Reward,
+    ) -> Result<()> {
+        let value = self.q_mut().update(alpha, state, action, target)?;
+        observer.on_update(&ValueUpdate {
+            state,
+            action,
+            target,
+            value,
+        });
+        Ok(())
+    }
+
+    /// Best available action with its value, ties broken by the config's rule
+    fn greedy(&self, state: E::Observation, env: &E) -> Result<(E::Action, Reward)> {
+        best(self, state, env, self.config().tie_breaking)
+    }
+
+    fn epsilon_greedy(&self, epsilon: f32, from: E::Observation, env: &E) -> Result<E::Action> {
+        let mut rng = random::rng();
+
+        if rng.gen::<f32>() < epsilon {
+            let available = env.available_actions(from);
+            if available.is_empty() {
+                return Err(Error::NoAvailableActions(format!("{:?}", from)));
+            }
+            Ok(available[rng.gen_range(0, available.len())])
+        } else {
+            Ok(self.greedy(from, env)?.0)
+        }
+    }
+
+    fn max_action_value(&self, state: E::Observation, env: &E) -> Result<Reward> {
+        // the value doesn't depend on which maximal action is picked
+        Ok(best(self, state, env, TieBreaking::First)?.1)
+    }
+}
+
+fn best<E: Environment, L: ApproximateLearner<E> + ?Sized>(
+    learner: &L,
+    state: E::Observation,
+    env: &E,
+    tie_breaking: TieBreaking,
+) -> Result<(E::Action, Reward)> {
+    let values = env
+        .available_actions(state)
+        .into_iter()
+        .map(|action| Ok((action, learner.q().value(state, action)?)))
+        .collect::<Result<Vec<(E::Action, Reward)>>>()?;
+    if values.iter().any(|(_, value)| value.is_nan()) {
+        return Err(Error::InvalidValue(format!("{:?}", state)));
+    }
+
+    tie_breaking
+        .argmax(values)
+        .ok_or_else(|| Error::NoAvailableActions(format!("{:?}", state)))
+}
+
+pub struct SemiGradientSarsa<E: Environment, F: Features<E::Observation>> {
+    pub config: TabularLearnerConfig,
+    q: LinearQ<E, F>,
+}
+
+impl<E: Environment, F: Features<E::Observation>> SemiGradientSarsa<E, F> {
+    pub fn new(config: TabularLearnerConfig, features: F) -> SemiGradientSarsa<E, F> {
+        SemiGradientSarsa {
+            config,
+            q: LinearQ::new(features),
+        }
+    }
+}
+
+impl<E: Environment, F: Features<E::Observation>> ApproximateLearner<E>
+    for SemiGradientSarsa<E, F>
+{
+    type Features = F;
+
+    // env is preinitialized
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        let mut action =
+            self.epsilon_greedy(self.config.epsilon, env.current_observation(), env)?;
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config.gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+
+            if terminal {
+                self.observed_update(observer, self.config.alpha, state, action, reward)?;
+                break;
+            }
+
+            let next_action = self.epsilon_greedy(self.config.epsilon, next_state, env)?;
+            let target = reward + self.config.gamma * self.q.value(next_state, next_action)?;
+            self.observed_update(observer, self.config.alpha, state, action, target)?;
+            if stats.truncate(self.config.max_steps) {
+                break;
+            }
+            state = next_state;
+            action = next_action;
+        }
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn q(&self) -> &LinearQ<E, F> {
+        &self.q
+    }
+
+    fn q_mut(&mut self) -> &mut LinearQ<E, F> {
+        &mut self.q
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
+
+pub struct SemiGradientQLearning<E: Environment, F: Features<E::Observation>> {
+    pub config: TabularLearnerConfig,
+    q: LinearQ<E, F>,
+}
+
+impl<E: Environment, F: Features<E::Observation>> SemiGradientQLearning<E, F> {
+    pub fn new(config: TabularLearnerConfig, features: F) -> SemiGradientQLearning<E, F> {
+        SemiGradientQLearning {
+            config,
+            q: LinearQ::new(features),
+        }
+    }
+}
+
+impl<E: Environment, F: Features<E::Observation>> ApproximateLearner<E>
+    for SemiGradientQLearning<E, F>
+{
+    type Features = F;
+
+    // env is preinitialized
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        let mut state = env.current_observation();
+        let mut stats = EpisodeStats::new();
+
+        loop {
+            let action = self.epsilon_greedy(self.config.epsilon, state, env)?;
+            let (next_state, reward) = env.take_action(action)?;
+            let terminal = env.is_terminal(next_state);
+            stats.record(reward, self.config.gamma, terminal);
+            observer.on_step(&Transition {
+                state,
+                action,
+                reward,
+                next_state,
+                terminal,
+            });
+
+            let next_value = if terminal {
+                0.
+            } else {
+                self.max_action_value(next_state, env)?
+            };
+            let target = reward + self.config.gamma * next_value;
+            self.observed_update(observer, self.config.alpha, state, action, target)?;
+
+            if terminal || stats.truncate(self.config.max_steps) {
+                break;
+            }
+            state = next_state;
+        }
+
+        observer.on_episode_end(&stats);
+        Ok(stats)
+    }
+
+    fn q(&self) -> &LinearQ<E, F> {
+        &self.q
+    }
+
+    fn q_mut(&mut self) -> &mut LinearQ<E, F> {
+        &mut self.q
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
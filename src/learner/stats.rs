@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::environment::Reward;
+
+/// Summary of a finished episode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeStats {
+    pub gain: Reward,              // undiscounted return
+    pub discounted_return: Reward, // under the learner's gamma
+    pub length: usize,             // steps taken
+    pub terminated: bool,          // reached a terminal state
+    pub truncated: bool,           // stopped early by a step limit
+}
+
+impl EpisodeStats {
+    pub fn new() -> EpisodeStats {
+        EpisodeStats::default()
+    }
+
+    /// Accounts for the reward of the next step
+    pub fn record(&mut self, reward: Reward, gamma: f32, terminal: bool) {
+        self.gain += reward;
+        self.discounted_return += gamma.powi(self.length as i32) * reward;
+        self.length += 1;
+        self.terminated = terminal;
+    }
+
+    /// Marks the episode as truncated once it has taken max_steps steps.
+    /// Returns whether it should stop
+    pub fn truncate(&mut self, max_steps: Option<usize>) -> bool {
+        self.truncated = !self.terminated && max_steps.is_some_and(|max| self.length >= max);
+        self.truncated
+    }
+}
@@ -0,0 +1,84 @@
+use crate::environment::{EnvTransition, Environment, Reward};
+use crate::learner::n_step::{reject_batch, validate};
+pub use crate::learner::n_step::{sigma_return, Bootstrap, NStepHistory, NStepLearner};
+pub use crate::learner::{
+    EpisodeStats, StepObserver, TabularLearner, TabularLearnerConfig, TabularLearnerData,
+};
+use crate::Result;
+
+/// n-step Tree Backup, off-policy without importance sampling. The target policy is
+/// epsilon-greedy with target_epsilon (0 for greedy)
+pub struct TreeBackup<E: Environment> {
+    pub config: TabularLearnerConfig,
+    pub target_epsilon: f32,
+    data: TabularLearnerData<E>,
+    history: NStepHistory<E::Observation, E::Action>,
+}
+
+impl<E: Environment> TreeBackup<E> {
+    pub fn new(
+        n: usize,
+        config: TabularLearnerConfig,
+        target_epsilon: f32,
+        terminal_state: Option<E::Observation>,
+    ) -> Result<TreeBackup<E>> {
+        validate(n, target_epsilon)?;
+
+        Ok(TreeBackup {
+            config,
+            target_epsilon,
+            data: TabularLearnerData::new(terminal_state),
+            history: NStepHistory::new(n),
+        })
+    }
+}
+
+impl<E: Environment> NStepLearner<E> for TreeBackup<E> {
+    fn history(&self) -> &NStepHistory<E::Observation, E::Action> {
+        &self.history
+    }
+
+    fn history_mut(&mut self) -> &mut NStepHistory<E::Observation, E::Action> {
+        &mut self.history
+    }
+
+    // Q(sigma) with sigma = 0 everywhere
+    fn n_step_target(
+        &self,
+        bootstrap: Option<Bootstrap<E::Observation, E::Action>>,
+        env: &E,
+    ) -> Result<(Reward, f32)> {
+        let target = sigma_return(self, self.target_epsilon, &|_| 0., bootstrap, env)?;
+        Ok((target, 1.))
+    }
+}
+
+impl<E: Environment> TabularLearner<E> for TreeBackup<E> {
+    fn episode_with(
+        &mut self,
+        env: &mut E,
+        observer: &mut dyn StepObserver<E>,
+    ) -> Result<EpisodeStats> {
+        self.n_step_episode(env, observer)
+    }
+
+    fn learn_batch(&mut self, _batch: &[EnvTransition<E>], _env: &E) -> Result<()> {
+        reject_batch()
+    }
+
+    fn data(&self) -> &TabularLearnerData<E> {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut TabularLearnerData<E> {
+        &mut self.data
+    }
+
+    fn config(&self) -> &TabularLearnerConfig {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut TabularLearnerConfig {
+        &mut self.config
+    }
+}
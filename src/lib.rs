@@ -1,2 +1,13 @@
+pub mod approximation;
+pub mod checkpoint;
 pub mod environment;
+pub mod error;
 pub mod learner;
+pub mod metrics;
+pub mod protocol;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random;
+pub mod sweep;
+
+pub use error::{Error, Result};
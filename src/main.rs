@@ -1,11 +1,15 @@
 use std::env;
 
 use rayon::prelude::*;
+use reinforcement_learning::environment::history::{HistoryMode, HistoryWrapper};
 use reinforcement_learning::environment::t_corridor::TCorridor;
 use reinforcement_learning::environment::Environment;
 use reinforcement_learning::environment::{m_wrapper::MWrapper, Reward};
 use reinforcement_learning::learner::{DynaQ, NStepSarsa, QLearning, Sarsa};
-use reinforcement_learning::learner::{TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::learner::{Printer, TabularLearner, TabularLearnerConfig};
+
+// history window long enough to see the cue from the split, for normal_steps = 6
+const HISTORY: usize = 7;
 
 fn main() {
     let episode_num: usize = if let Some(arg1) = env::args().nth(1) {
@@ -20,35 +24,39 @@ fn main() {
     let print_samples = true;
     let sample_num = 100;
 
-    let mut config = TabularLearnerConfig::new(0.1, 0.05, 0.8, 10.);
-    config.debug = false;
+    let config = TabularLearnerConfig::new(0.1, 0.05, 0.8, 10.);
     let sarsa_n = 7;
     let dynaq_n = 10; // planning steps
 
     let terminal = TCorridor::new(normal_steps).get_terminal();
     let m_terminal = wrap_t_corridor(normal_steps).get_terminal();
+    let h_terminal = history_t_corridor(normal_steps).get_terminal();
 
-    let mut sarsa = Sarsa::<TCorridor>::new(config.clone(), terminal);
+    // learners of the commented out experiments, kept around for quick toggling
+    let mut _sarsa = Sarsa::<TCorridor>::new(config.clone(), terminal);
     let mut m_sarsa = Sarsa::<MWrapper<TCorridor>>::new(config.clone(), m_terminal);
-    let mut ql = QLearning::<TCorridor>::new(config.clone(), terminal);
-    let mut m_ql = QLearning::<MWrapper<TCorridor>>::new(config.clone(), m_terminal);
-    let mut dynaq = DynaQ::<TCorridor>::new(config.clone(), dynaq_n, terminal);
-    let mut m_dynaq = DynaQ::<MWrapper<TCorridor>>::new(config.clone(), dynaq_n, m_terminal);
-    let mut n_sarsa = NStepSarsa::<TCorridor>::new(sarsa_n, config.clone(), terminal);
-    let mut m_n_sarsa = NStepSarsa::<MWrapper<TCorridor>>::new(sarsa_n, config.clone(), m_terminal);
+    let mut _ql = QLearning::<TCorridor>::new(config.clone(), terminal);
+    let mut _m_ql = QLearning::<MWrapper<TCorridor>>::new(config.clone(), m_terminal);
+    let mut _dynaq = DynaQ::<TCorridor>::new(config.clone(), dynaq_n, terminal);
+    let mut _m_dynaq = DynaQ::<MWrapper<TCorridor>>::new(config.clone(), dynaq_n, m_terminal);
+    let mut _n_sarsa = NStepSarsa::<TCorridor>::new(sarsa_n, config.clone(), terminal).unwrap();
+    let mut m_n_sarsa =
+        NStepSarsa::<MWrapper<TCorridor>>::new(sarsa_n, config.clone(), m_terminal).unwrap();
+    let mut _h_sarsa = Sarsa::<HistoryWrapper<TCorridor, HISTORY>>::new(config.clone(), h_terminal);
 
     {
         let mut tasks = vec![
-            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut sarsa))
+            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut _sarsa))
             //     as Box<dyn FnMut() + Send>,
             Box::new(|| train(|| wrap_t_corridor(normal_steps), episode_num, &mut m_sarsa))
                 as Box<dyn FnMut() + Send>,
-            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut ql)),
-            // Box::new(|| train(|| wrap_t_corridor(normal_steps), episode_num, &mut m_ql)),
-            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut dynaq)),
-            // Box::new(|| train(|| wrap_t_corridor(normal_steps), episode_num, &mut m_dynaq)),
-            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut n_sarsa))
+            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut _ql)),
+            // Box::new(|| train(|| wrap_t_corridor(normal_steps), episode_num, &mut _m_ql)),
+            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut _dynaq)),
+            // Box::new(|| train(|| wrap_t_corridor(normal_steps), episode_num, &mut _m_dynaq)),
+            // Box::new(|| train(|| TCorridor::new(normal_steps), episode_num, &mut _n_sarsa))
             //     as Box<dyn FnMut() + Send>,
+            // Box::new(|| train(|| history_t_corridor(normal_steps), episode_num, &mut _h_sarsa)),
             Box::new(|| {
                 train(
                     || wrap_t_corridor(normal_steps),
@@ -77,7 +85,7 @@ fn main() {
     //     print_samples,
     //     || TCorridor::new(normal_steps),
     //     sample_num,
-    //     &mut sarsa,
+    //     &mut _sarsa,
     // );
     // println!();
 
@@ -86,7 +94,7 @@ fn main() {
     //     print_samples,
     //     || wrap_t_corridor(normal_steps),
     //     sample_num,
-    //     &mut m_ql,
+    //     &mut _m_ql,
     // );
     // println!();
 
@@ -95,7 +103,7 @@ fn main() {
     //     print_samples,
     //     || TCorridor::new(normal_steps),
     //     sample_num,
-    //     &mut ql,
+    //     &mut _ql,
     // );
     // println!();
 
@@ -104,7 +112,7 @@ fn main() {
     //     print_samples,
     //     || wrap_t_corridor(normal_steps),
     //     10,
-    //     &mut m_dynaq,
+    //     &mut _m_dynaq,
     // );
     // println!();
 
@@ -113,7 +121,7 @@ fn main() {
     //     print_samples,
     //     || TCorridor::new(normal_steps),
     //     10,
-    //     &mut dynaq,
+    //     &mut _dynaq,
     // );
     // println!();
 
@@ -126,12 +134,21 @@ fn main() {
     );
     println!();
 
+    // println!("Sample H-SARSA episodes: ");
+    // sample_episodes(
+    //     print_samples,
+    //     || history_t_corridor(normal_steps),
+    //     sample_num,
+    //     &mut _h_sarsa,
+    // );
+    // println!();
+
     // println!("Sample N-SARSA episodes: ");
     // sample_episodes(
     //     print_samples,
     //     || TCorridor::new(normal_steps),
     //     sample_num,
-    //     &mut n_sarsa,
+    //     &mut _n_sarsa,
     // );
     // println!();
 
@@ -145,8 +162,7 @@ fn main() {
 }
 
 fn print_q<E: Environment, L: TabularLearner<E>>(learner: &L) {
-    let mut q: Vec<((E::State, E::Action), Reward)> =
-        learner.data().q.clone().into_iter().collect();
+    let mut q: Vec<_> = learner.data().q.iter().collect();
 
     q.sort_by_key(|k| k.0);
 
@@ -175,7 +191,10 @@ where
             );
         }
         let mut env = new();
-        learner.episode(&mut env);
+        if let Err(e) = learner.episode(&mut env) {
+            eprintln!("episode {}/{} failed: {}", i, episode_num, e);
+            return;
+        }
     }
 }
 
@@ -189,14 +208,24 @@ fn sample_episodes<E: Environment, F, L: TabularLearner<E>>(
 {
     let mut total_gain: Reward = 0.0;
     learner.config_mut().epsilon = 0.;
-    learner.config_mut().debug = print_samples;
 
     for i in 0..episode_num {
         if print_samples {
             println!("Sample episode {}/{}", i + 1, episode_num);
         }
         let mut env = new();
-        let gain = learner.episode(&mut env);
+        let result = if print_samples {
+            learner.episode_with(&mut env, &mut Printer { updates: false })
+        } else {
+            learner.episode(&mut env)
+        };
+        let gain = match result {
+            Ok(stats) => stats.gain,
+            Err(e) => {
+                println!("Sample episode failed: {}", e);
+                return;
+            }
+        };
         println!("Gain: {}", gain);
         total_gain += gain;
     }
@@ -207,3 +236,7 @@ fn sample_episodes<E: Environment, F, L: TabularLearner<E>>(
 fn wrap_t_corridor(normal_steps: usize) -> MWrapper<TCorridor> {
     MWrapper::new(TCorridor::new(normal_steps))
 }
+
+fn history_t_corridor(normal_steps: usize) -> HistoryWrapper<TCorridor, HISTORY> {
+    HistoryWrapper::new(TCorridor::new(normal_steps), HistoryMode::Observations)
+}
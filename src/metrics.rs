@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::environment::{Environment, Reward};
+use crate::learner::{EpisodeStats, TabularLearner};
+use crate::Result;
+
+/// Reference action values Q*, e.g. obtained by planning or from a long training run
+pub type ReferenceQ<E> =
+    HashMap<(<E as Environment>::Observation, <E as Environment>::Action), Reward>;
+
+/// Measurements of a learner against a reference solution, taken after an episode
+#[derive(Clone, Copy, Debug)]
+pub struct EpisodeMetrics {
+    pub stats: EpisodeStats,
+    pub q_rmse: f32,
+    pub regret: f32,
+}
+
+/// Root mean squared error of the learner's Q over all pairs present in the reference
+pub fn q_rmse<E: Environment, L: TabularLearner<E>>(learner: &L, reference: &ReferenceQ<E>) -> f32 {
+    if reference.is_empty() {
+        return 0.;
+    }
+
+    let squared_error: f32 = reference
+        .iter()
+        .map(|(&(state, action), &reference_value)| {
+            let value = learner.data().value(learner.config(), state, action);
+            (value - reference_value).powi(2)
+        })
+        .sum();
+
+    (squared_error / reference.len() as f32).sqrt()
+}
+
+/// Mean of V*(s) - Q*(s, π(s)) over states in the reference, where π is greedy w.r.t. the learner.
+/// States whose greedy action has no reference value are skipped.
+pub fn greedy_regret<E: Environment, L: TabularLearner<E>>(
+    learner: &L,
+    reference: &ReferenceQ<E>,
+    env: &E,
+) -> Result<f32> {
+    let mut states: Vec<E::Observation> = reference.keys().map(|(state, _)| *state).collect();
+    states.sort();
+    states.dedup();
+
+    let mut total_regret = 0.;
+    let mut counted = 0;
+
+    for state in states {
+        let optimal = env
+            .available_actions(state)
+            .iter()
+            .filter_map(|action| reference.get(&(state, *action)))
+            .fold(Reward::NEG_INFINITY, |max, value| max.max(*value));
+
+        let greedy = learner.epsilon_greedy(0., state, env)?;
+        if let Some(greedy_value) = reference.get(&(state, greedy)) {
+            total_regret += optimal - greedy_value;
+            counted += 1;
+        }
+    }
+
+    if counted == 0 {
+        Ok(0.)
+    } else {
+        Ok(total_regret / counted as f32)
+    }
+}
+
+/// Runs a single training episode and measures the learner against the reference afterwards
+pub fn tracked_episode<E: Environment, L: TabularLearner<E>>(
+    learner: &mut L,
+    env: &mut E,
+    reference: &ReferenceQ<E>,
+) -> Result<EpisodeMetrics> {
+    let stats = learner.episode(env)?;
+
+    Ok(EpisodeMetrics {
+        stats,
+        q_rmse: q_rmse(learner, reference),
+        regret: greedy_regret(learner, reference, env)?,
+    })
+}
@@ -0,0 +1,127 @@
+//! Line-delimited JSON protocol for driving an `Environment` from another process.
+//! Each request is one line like `{"command":"step","action":"Forward"}`, answered by one
+//! line like `{"type":"step","observation":"Start","reward":-5.0,"terminal":false}`.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+use crate::environment::{Environment, Reward};
+use crate::Result;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request<O, A> {
+    /// Starts a new episode
+    Reset,
+    Step {
+        action: A,
+    },
+    /// Available actions in observation, the current one if missing
+    Actions {
+        observation: Option<O>,
+    },
+    Observation,
+    /// The terminal observation, if the task has one
+    Terminal,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response<O, A> {
+    Reset {
+        observation: O,
+        terminal: bool,
+    },
+    Step {
+        observation: O,
+        reward: Reward,
+        terminal: bool,
+    },
+    Actions {
+        actions: Vec<A>,
+    },
+    Observation {
+        observation: O,
+        terminal: bool,
+    },
+    Terminal {
+        observation: Option<O>,
+    },
+    /// The request failed, the connection stays usable
+    Error {
+        message: String,
+    },
+}
+
+pub type EnvRequest<E> = Request<<E as Environment>::Observation, <E as Environment>::Action>;
+pub type EnvResponse<E> = Response<<E as Environment>::Observation, <E as Environment>::Action>;
+
+/// Answers a single request, creating a new environment with `new` on reset
+pub fn respond<E: Environment, F: Fn() -> E>(
+    env: &mut E,
+    new: &F,
+    request: EnvRequest<E>,
+) -> EnvResponse<E> {
+    match request {
+        Request::Reset => {
+            *env = new();
+            Response::Reset {
+                observation: env.current_observation(),
+                terminal: env.terminated(),
+            }
+        }
+        Request::Step { action } => match env.take_action(action) {
+            Ok((observation, reward)) => Response::Step {
+                observation,
+                reward,
+                terminal: env.is_terminal(observation),
+            },
+            Err(e) => Response::Error {
+                message: e.to_string(),
+            },
+        },
+        Request::Actions { observation } => Response::Actions {
+            actions: env
+                .available_actions(observation.unwrap_or_else(|| env.current_observation())),
+        },
+        Request::Observation => Response::Observation {
+            observation: env.current_observation(),
+            terminal: env.terminated(),
+        },
+        Request::Terminal => Response::Terminal {
+            observation: env.get_terminal(),
+        },
+    }
+}
+
+/// Serves requests from reader until it is closed. Malformed lines get an error response
+pub fn serve<E, F, R, W>(new: F, reader: R, mut writer: W) -> Result<()>
+where
+    E: Environment,
+    E::Observation: Serialize + DeserializeOwned,
+    E::Action: Serialize + DeserializeOwned,
+    F: Fn() -> E,
+    R: BufRead,
+    W: Write,
+{
+    let mut env = new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<EnvRequest<E>>(&line) {
+            Ok(request) => respond(&mut env, &new, request),
+            Err(e) => Response::Error {
+                message: format!("malformed request: {}", e),
+            },
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
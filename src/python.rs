@@ -0,0 +1,602 @@
+//! Python bindings (`python` feature). Environments follow the Gymnasium `reset`/`step` API,
+//! observations and actions are plain Python values: strings for unit variants, tuples for
+//! tuples and dicts for data-carrying variants.
+// clippy misreads the error conversions generated by #[pymethods]
+#![allow(clippy::useless_conversion)]
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::environment::gridworld::GridWorld as RustGridWorld;
+use crate::environment::gridworld_definitions::cliff;
+use crate::environment::m_wrapper::MWrapper as RustMWrapper;
+use crate::environment::t_corridor::TCorridor as RustTCorridor;
+use crate::environment::{Environment, Reward};
+use crate::learner::{self, EpisodeStats, TabularLearner, TabularLearnerConfig};
+use crate::{random, Error};
+
+impl From<Error> for PyErr {
+    fn from(e: Error) -> PyErr {
+        PyRuntimeError::new_err(e.to_string())
+    }
+}
+
+fn to_py(py: Python<'_>, value: &Value) -> PyObject {
+    match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        // tuples keep observations hashable
+        Value::Array(values) => {
+            PyTuple::new_bound(py, values.iter().map(|v| to_py(py, v))).into_py(py)
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new_bound(py);
+            for (k, v) in fields {
+                dict.set_item(k, to_py(py, v))
+                    .expect("str keys are hashable");
+            }
+            dict.into_py(py)
+        }
+    }
+}
+
+fn from_py(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if object.is_none() {
+        Ok(Value::Null)
+    } else if object.is_instance_of::<PyBool>() {
+        Ok(Value::Bool(object.extract()?))
+    } else if object.is_instance_of::<PyInt>() {
+        Ok(Value::Number(object.extract::<i64>()?.into()))
+    } else if object.is_instance_of::<PyFloat>() {
+        let number = Number::from_f64(object.extract()?)
+            .ok_or_else(|| PyValueError::new_err("NaN and infinities are not valid values"))?;
+        Ok(Value::Number(number))
+    } else if object.is_instance_of::<PyString>() {
+        Ok(Value::String(object.extract()?))
+    } else if let Ok(tuple) = object.downcast::<PyTuple>() {
+        Ok(Value::Array(
+            tuple.iter().map(|v| from_py(&v)).collect::<PyResult<_>>()?,
+        ))
+    } else if let Ok(list) = object.downcast::<PyList>() {
+        Ok(Value::Array(
+            list.iter().map(|v| from_py(&v)).collect::<PyResult<_>>()?,
+        ))
+    } else if let Ok(dict) = object.downcast::<PyDict>() {
+        let mut fields = Map::new();
+        for (k, v) in dict.iter() {
+            fields.insert(k.extract()?, from_py(&v)?);
+        }
+        Ok(Value::Object(fields))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "can't convert {} to an observation or action",
+            object.get_type().name()?
+        )))
+    }
+}
+
+fn serialize<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(Error::from)?;
+    Ok(to_py(py, &value))
+}
+
+fn deserialize<T: DeserializeOwned>(object: &Bound<'_, PyAny>) -> PyResult<T> {
+    serde_json::from_value(from_py(object)?)
+        .map_err(|e| PyValueError::new_err(format!("invalid value: {}", e)))
+}
+
+/// How to build an environment, kept to start new episodes on reset
+#[derive(Clone, Debug)]
+enum Spec {
+    Cliff { width: i32, height: i32 },
+    TCorridor { normal_steps: usize },
+    MWrapper(Box<Spec>),
+}
+
+enum AnyEnv {
+    Cliff(RustGridWorld),
+    TCorridor(RustTCorridor),
+    MCliff(RustMWrapper<RustGridWorld>),
+    MTCorridor(RustMWrapper<RustTCorridor>),
+}
+
+macro_rules! with_env {
+    ($any:expr, $env:ident => $body:expr) => {
+        match $any {
+            AnyEnv::Cliff($env) => $body,
+            AnyEnv::TCorridor($env) => $body,
+            AnyEnv::MCliff($env) => $body,
+            AnyEnv::MTCorridor($env) => $body,
+        }
+    };
+}
+
+impl Spec {
+    fn build(&self) -> PyResult<AnyEnv> {
+        Ok(match self {
+            Spec::Cliff { width, height } => {
+                AnyEnv::Cliff(RustGridWorld::new(cliff(*width, *height)))
+            }
+            Spec::TCorridor { normal_steps } => {
+                AnyEnv::TCorridor(RustTCorridor::new(*normal_steps))
+            }
+            Spec::MWrapper(inner) => match inner.build()? {
+                AnyEnv::Cliff(env) => AnyEnv::MCliff(RustMWrapper::new(env)),
+                AnyEnv::TCorridor(env) => AnyEnv::MTCorridor(RustMWrapper::new(env)),
+                _ => return Err(PyValueError::new_err("memory wrappers can't be nested")),
+            },
+        })
+    }
+}
+
+fn observation<E: Environment>(py: Python<'_>, env: &E) -> PyResult<PyObject>
+where
+    E::Observation: Serialize,
+{
+    serialize(py, &env.current_observation())
+}
+
+fn step<E: Environment>(
+    py: Python<'_>,
+    env: &mut E,
+    action: &Bound<'_, PyAny>,
+) -> PyResult<(PyObject, Reward, bool)>
+where
+    E::Observation: Serialize,
+    E::Action: DeserializeOwned,
+{
+    let (observation, reward) = env.take_action(deserialize(action)?)?;
+    Ok((
+        serialize(py, &observation)?,
+        reward,
+        env.is_terminal(observation),
+    ))
+}
+
+fn actions<E: Environment>(
+    py: Python<'_>,
+    env: &E,
+    observation: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Vec<PyObject>>
+where
+    E::Observation: DeserializeOwned,
+    E::Action: Serialize,
+{
+    let observation = match observation {
+        Some(observation) => deserialize(observation)?,
+        None => env.current_observation(),
+    };
+    env.available_actions(observation)
+        .iter()
+        .map(|action| serialize(py, action))
+        .collect()
+}
+
+/// Base class of all environments. Episodes reaching max_steps steps are reported as truncated
+#[pyclass(subclass, module = "reinforcement_learning")]
+pub struct Env {
+    spec: Spec,
+    env: AnyEnv,
+    #[pyo3(get, set)]
+    max_steps: Option<usize>,
+    stats: EpisodeStats, // of the current episode
+}
+
+impl Env {
+    fn new(spec: Spec, max_steps: Option<usize>) -> PyResult<Env> {
+        Ok(Env {
+            env: spec.build()?,
+            spec,
+            max_steps,
+            stats: EpisodeStats::new(),
+        })
+    }
+}
+
+#[pymethods]
+impl Env {
+    /// Starts a new episode, returning (observation, info)
+    #[pyo3(signature = (seed=None, options=None))]
+    fn reset(
+        &mut self,
+        py: Python<'_>,
+        seed: Option<u64>,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(PyObject, PyObject)> {
+        let _ = options; // accepted for Gymnasium compatibility
+        if let Some(seed) = seed {
+            random::seed(seed); // also seeds learners running on this thread
+        }
+        self.env = self.spec.build()?;
+        self.stats = EpisodeStats::new();
+        Ok((self.observation(py)?, PyDict::new_bound(py).into_py(py)))
+    }
+
+    /// Returns (observation, reward, terminated, truncated, info)
+    fn step(
+        &mut self,
+        py: Python<'_>,
+        action: &Bound<'_, PyAny>,
+    ) -> PyResult<(PyObject, Reward, bool, bool, PyObject)> {
+        let (observation, reward, terminated) =
+            with_env!(&mut self.env, env => step(py, env, action))?;
+        self.stats.record(reward, 1., terminated);
+        let truncated = self.stats.truncate(self.max_steps);
+        Ok((
+            observation,
+            reward,
+            terminated,
+            truncated,
+            PyDict::new_bound(py).into_py(py),
+        ))
+    }
+
+    /// Actions available in observation, the current one by default
+    #[pyo3(signature = (observation=None))]
+    fn available_actions(
+        &self,
+        py: Python<'_>,
+        observation: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Vec<PyObject>> {
+        with_env!(&self.env, env => actions(py, env, observation))
+    }
+
+    fn observation(&self, py: Python<'_>) -> PyResult<PyObject> {
+        with_env!(&self.env, env => observation(py, env))
+    }
+
+    /// The true state, which may hold more than the observation
+    fn state(&self, py: Python<'_>) -> PyResult<PyObject> {
+        with_env!(&self.env, env => serialize(py, &env.current_state()))
+    }
+
+    #[getter]
+    fn terminated(&self) -> bool {
+        with_env!(&self.env, env => env.terminated())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.spec)
+    }
+}
+
+/// Cliff walking gridworld
+#[pyclass(extends = Env, module = "reinforcement_learning")]
+pub struct GridWorld;
+
+#[pymethods]
+impl GridWorld {
+    #[new]
+    #[pyo3(signature = (width=12, height=4, max_steps=None))]
+    fn new(width: i32, height: i32, max_steps: Option<usize>) -> PyResult<(GridWorld, Env)> {
+        Ok((
+            GridWorld,
+            Env::new(Spec::Cliff { width, height }, max_steps)?,
+        ))
+    }
+}
+
+#[pyclass(extends = Env, module = "reinforcement_learning")]
+pub struct TCorridor;
+
+#[pymethods]
+impl TCorridor {
+    #[new]
+    #[pyo3(signature = (normal_steps=6, max_steps=None))]
+    fn new(normal_steps: usize, max_steps: Option<usize>) -> PyResult<(TCorridor, Env)> {
+        Ok((
+            TCorridor,
+            Env::new(Spec::TCorridor { normal_steps }, max_steps)?,
+        ))
+    }
+}
+
+/// Adds a memory bit to a GridWorld or TCorridor, keeping its max_steps
+#[pyclass(extends = Env, module = "reinforcement_learning")]
+pub struct MWrapper;
+
+#[pymethods]
+impl MWrapper {
+    #[new]
+    fn new(env: PyRef<'_, Env>) -> PyResult<(MWrapper, Env)> {
+        Ok((
+            MWrapper,
+            Env::new(Spec::MWrapper(Box::new(env.spec.clone())), env.max_steps)?,
+        ))
+    }
+}
+
+type Boxed<E> = Box<dyn TabularLearner<E> + Send>;
+
+enum AnyLearner {
+    Cliff(Boxed<RustGridWorld>),
+    TCorridor(Boxed<RustTCorridor>),
+    MCliff(Boxed<RustMWrapper<RustGridWorld>>),
+    MTCorridor(Boxed<RustMWrapper<RustTCorridor>>),
+}
+
+macro_rules! with_learner {
+    ($any:expr, $learner:ident => $body:expr) => {
+        match $any {
+            AnyLearner::Cliff($learner) => $body,
+            AnyLearner::TCorridor($learner) => $body,
+            AnyLearner::MCliff($learner) => $body,
+            AnyLearner::MTCorridor($learner) => $body,
+        }
+    };
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    QLearning,
+    Sarsa,
+    NStepSarsa(usize),
+    DynaQ(u32),
+}
+
+fn boxed<E: Environment + 'static>(
+    kind: Kind,
+    config: TabularLearnerConfig,
+    env: &E,
+) -> PyResult<Boxed<E>>
+where
+    E::Observation: Send,
+    E::Action: Send,
+{
+    let terminal = env.get_terminal();
+    Ok(match kind {
+        Kind::QLearning => Box::new(learner::QLearning::new(config, terminal)),
+        Kind::Sarsa => Box::new(learner::Sarsa::new(config, terminal)),
+        Kind::NStepSarsa(n) => Box::new(learner::NStepSarsa::new(n, config, terminal)?),
+        Kind::DynaQ(n) => Box::new(learner::DynaQ::new(config, n, terminal)),
+    })
+}
+
+fn episodes<E: Environment, F: Fn() -> PyResult<E>>(
+    learner: &mut Boxed<E>,
+    new: F,
+    n: usize,
+) -> PyResult<Vec<Reward>> {
+    (0..n)
+        .map(|_| Ok(learner.episode(&mut new()?)?.gain))
+        .collect()
+}
+
+fn act<E: Environment>(
+    py: Python<'_>,
+    learner: &Boxed<E>,
+    env: &E,
+    observation: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyObject>
+where
+    E::Observation: DeserializeOwned,
+    E::Action: Serialize,
+{
+    let observation = match observation {
+        Some(observation) => deserialize(observation)?,
+        None => env.current_observation(),
+    };
+    let action = learner.epsilon_greedy(learner.config().epsilon, observation, env)?;
+    serialize(py, &action)
+}
+
+fn q<E: Environment>(
+    py: Python<'_>,
+    learner: &Boxed<E>,
+) -> PyResult<Vec<(PyObject, PyObject, Reward)>>
+where
+    E::Observation: Serialize,
+    E::Action: Serialize,
+{
+    let mut q: Vec<_> = learner.data().q.iter().collect();
+    q.sort_by_key(|(k, _)| *k);
+    q.into_iter()
+        .map(|((state, action), value)| {
+            Ok((serialize(py, &state)?, serialize(py, &action)?, value))
+        })
+        .collect()
+}
+
+fn mismatch() -> PyErr {
+    PyValueError::new_err("the learner was created for a different kind of environment")
+}
+
+/// Base class of the tabular learners, each bound to the kind of environment it was created for
+#[pyclass(subclass, module = "reinforcement_learning")]
+pub struct Learner {
+    learner: AnyLearner,
+}
+
+impl Learner {
+    fn new(kind: Kind, env: &Env, config: TabularLearnerConfig) -> PyResult<Learner> {
+        let learner = match &env.env {
+            AnyEnv::Cliff(env) => AnyLearner::Cliff(boxed(kind, config, env)?),
+            AnyEnv::TCorridor(env) => AnyLearner::TCorridor(boxed(kind, config, env)?),
+            AnyEnv::MCliff(env) => AnyLearner::MCliff(boxed(kind, config, env)?),
+            AnyEnv::MTCorridor(env) => AnyLearner::MTCorridor(boxed(kind, config, env)?),
+        };
+        Ok(Learner { learner })
+    }
+}
+
+#[pymethods]
+impl Learner {
+    /// Trains for n episodes on fresh copies of env, returning the gain of each.
+    /// env itself is left untouched
+    #[pyo3(signature = (env, n=1))]
+    fn train(&mut self, py: Python<'_>, env: PyRef<'_, Env>, n: usize) -> PyResult<Vec<Reward>> {
+        let spec = env.spec.clone();
+        let learner = &mut self.learner;
+        py.allow_threads(move || {
+            macro_rules! run {
+                ($variant:ident) => {
+                    match learner {
+                        AnyLearner::$variant(learner) => episodes(
+                            learner,
+                            || match spec.build()? {
+                                AnyEnv::$variant(env) => Ok(env),
+                                _ => Err(mismatch()),
+                            },
+                            n,
+                        ),
+                        _ => unreachable!(),
+                    }
+                };
+            }
+            match learner {
+                AnyLearner::Cliff(_) => run!(Cliff),
+                AnyLearner::TCorridor(_) => run!(TCorridor),
+                AnyLearner::MCliff(_) => run!(MCliff),
+                AnyLearner::MTCorridor(_) => run!(MTCorridor),
+            }
+        })
+    }
+
+    /// Epsilon-greedy action in observation, the current one of env by default
+    #[pyo3(signature = (env, observation=None))]
+    fn act(
+        &self,
+        py: Python<'_>,
+        env: PyRef<'_, Env>,
+        observation: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        match (&self.learner, &env.env) {
+            (AnyLearner::Cliff(l), AnyEnv::Cliff(e)) => act(py, l, e, observation),
+            (AnyLearner::TCorridor(l), AnyEnv::TCorridor(e)) => act(py, l, e, observation),
+            (AnyLearner::MCliff(l), AnyEnv::MCliff(e)) => act(py, l, e, observation),
+            (AnyLearner::MTCorridor(l), AnyEnv::MTCorridor(e)) => act(py, l, e, observation),
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// The Q table as (observation, action, value) triples
+    fn q(&self, py: Python<'_>) -> PyResult<Vec<(PyObject, PyObject, Reward)>> {
+        with_learner!(&self.learner, learner => q(py, learner))
+    }
+
+    #[getter]
+    fn get_alpha(&self) -> f32 {
+        with_learner!(&self.learner, learner => learner.config().alpha)
+    }
+
+    #[setter]
+    fn set_alpha(&mut self, alpha: f32) {
+        with_learner!(&mut self.learner, learner => learner.config_mut().alpha = alpha)
+    }
+
+    #[getter]
+    fn get_epsilon(&self) -> f32 {
+        with_learner!(&self.learner, learner => learner.config().epsilon)
+    }
+
+    #[setter]
+    fn set_epsilon(&mut self, epsilon: f32) {
+        with_learner!(&mut self.learner, learner => learner.config_mut().epsilon = epsilon)
+    }
+
+    #[getter]
+    fn get_gamma(&self) -> f32 {
+        with_learner!(&self.learner, learner => learner.config().gamma)
+    }
+}
+
+#[pyclass(extends = Learner, module = "reinforcement_learning")]
+pub struct QLearning;
+
+#[pymethods]
+impl QLearning {
+    #[new]
+    #[pyo3(signature = (env, alpha=0.1, epsilon=0.1, gamma=1.0, initial_q=0.0))]
+    fn new(
+        env: PyRef<'_, Env>,
+        alpha: f32,
+        epsilon: f32,
+        gamma: f32,
+        initial_q: Reward,
+    ) -> PyResult<(QLearning, Learner)> {
+        let config = TabularLearnerConfig::new(alpha, epsilon, gamma, initial_q);
+        Ok((QLearning, Learner::new(Kind::QLearning, &env, config)?))
+    }
+}
+
+#[pyclass(extends = Learner, module = "reinforcement_learning")]
+pub struct Sarsa;
+
+#[pymethods]
+impl Sarsa {
+    #[new]
+    #[pyo3(signature = (env, alpha=0.1, epsilon=0.1, gamma=1.0, initial_q=0.0))]
+    fn new(
+        env: PyRef<'_, Env>,
+        alpha: f32,
+        epsilon: f32,
+        gamma: f32,
+        initial_q: Reward,
+    ) -> PyResult<(Sarsa, Learner)> {
+        let config = TabularLearnerConfig::new(alpha, epsilon, gamma, initial_q);
+        Ok((Sarsa, Learner::new(Kind::Sarsa, &env, config)?))
+    }
+}
+
+#[pyclass(extends = Learner, module = "reinforcement_learning")]
+pub struct NStepSarsa;
+
+#[pymethods]
+impl NStepSarsa {
+    #[new]
+    #[pyo3(signature = (env, n, alpha=0.1, epsilon=0.1, gamma=1.0, initial_q=0.0))]
+    fn new(
+        env: PyRef<'_, Env>,
+        n: usize,
+        alpha: f32,
+        epsilon: f32,
+        gamma: f32,
+        initial_q: Reward,
+    ) -> PyResult<(NStepSarsa, Learner)> {
+        let config = TabularLearnerConfig::new(alpha, epsilon, gamma, initial_q);
+        Ok((NStepSarsa, Learner::new(Kind::NStepSarsa(n), &env, config)?))
+    }
+}
+
+#[pyclass(extends = Learner, module = "reinforcement_learning")]
+pub struct DynaQ;
+
+#[pymethods]
+impl DynaQ {
+    #[new]
+    #[pyo3(signature = (env, n, alpha=0.1, epsilon=0.1, gamma=1.0, initial_q=0.0))]
+    fn new(
+        env: PyRef<'_, Env>,
+        n: u32,
+        alpha: f32,
+        epsilon: f32,
+        gamma: f32,
+        initial_q: Reward,
+    ) -> PyResult<(DynaQ, Learner)> {
+        let config = TabularLearnerConfig::new(alpha, epsilon, gamma, initial_q);
+        Ok((DynaQ, Learner::new(Kind::DynaQ(n), &env, config)?))
+    }
+}
+
+#[pymodule]
+fn reinforcement_learning(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Env>()?;
+    m.add_class::<GridWorld>()?;
+    m.add_class::<TCorridor>()?;
+    m.add_class::<MWrapper>()?;
+    m.add_class::<Learner>()?;
+    m.add_class::<QLearning>()?;
+    m.add_class::<Sarsa>()?;
+    m.add_class::<NStepSarsa>()?;
+    m.add_class::<DynaQ>()?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Per-thread random number generator used everywhere in the crate instead of `thread_rng`,
+//! so runs can be reproduced by seeding the thread they run on.
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<Pcg64> = RefCell::new(Pcg64::from_entropy());
+}
+
+/// Reseeds the current thread's generator
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = Pcg64::seed_from_u64(seed));
+}
+
+/// Copy of the current thread's generator state, for checkpoints
+pub fn snapshot() -> Pcg64 {
+    RNG.with(|rng| rng.borrow().clone())
+}
+
+/// Continues from a state taken with `snapshot`
+pub fn restore(state: Pcg64) {
+    RNG.with(|rng| *rng.borrow_mut() = state);
+}
+
+/// Runs f with generator as the current thread's generator, e.g. to give each of several
+/// environments stepped on a thread pool its own reproducible stream
+pub fn with_generator<T>(generator: &mut Pcg64, f: impl FnOnce() -> T) -> T {
+    RNG.with(|rng| std::mem::swap(&mut *rng.borrow_mut(), generator));
+    let result = f();
+    RNG.with(|rng| std::mem::swap(&mut *rng.borrow_mut(), generator));
+    result
+}
+
+/// Handle to the current thread's generator, use like `rand::thread_rng()`
+pub fn rng() -> LocalRng {
+    LocalRng
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LocalRng;
+
+impl RngCore for LocalRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
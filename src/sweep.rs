@@ -0,0 +1,361 @@
+//! Hyperparameter sweeps: every configuration is trained from scratch once per seed, all runs
+//! in parallel, and configurations are ranked by their final greedy return.
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::environment::{Environment, Reward};
+use crate::learner::{DynaQ, NStepSarsa, QLearning, Sarsa, TabularLearner, TabularLearnerConfig};
+use crate::{random, Error, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    QLearning,
+    Sarsa,
+    NStepSarsa, // n is the number of steps
+    DynaQ,      // n is the number of planning steps
+}
+
+impl Algorithm {
+    pub fn learner<E: Environment + 'static>(
+        self,
+        params: &Params,
+        terminal_state: Option<E::Observation>,
+    ) -> Result<Box<dyn TabularLearner<E> + Send>>
+    where
+        E::Observation: Send,
+        E::Action: Send,
+    {
+        let config =
+            TabularLearnerConfig::new(params.alpha, params.epsilon, params.gamma, params.initial_q);
+        Ok(match self {
+            Algorithm::QLearning => Box::new(QLearning::new(config, terminal_state)),
+            Algorithm::Sarsa => Box::new(Sarsa::new(config, terminal_state)),
+            Algorithm::NStepSarsa => Box::new(NStepSarsa::new(params.n, config, terminal_state)?),
+            Algorithm::DynaQ => Box::new(DynaQ::new(config, params.n as u32, terminal_state)),
+        })
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Algorithm> {
+        match s {
+            "q_learning" => Ok(Algorithm::QLearning),
+            "sarsa" => Ok(Algorithm::Sarsa),
+            "n_step_sarsa" => Ok(Algorithm::NStepSarsa),
+            "dyna_q" => Ok(Algorithm::DynaQ),
+            _ => Err(Error::InvalidConfig(format!("unknown algorithm {}", s))),
+        }
+    }
+}
+
+/// Values a hyperparameter can take
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamRange {
+    Values(Vec<f32>),
+    /// Closed interval, only usable in random search
+    Uniform(f32, f32),
+}
+
+impl ParamRange {
+    fn values(&self, name: &str) -> Result<Vec<f32>> {
+        match self {
+            ParamRange::Values(values) if !values.is_empty() => Ok(values.clone()),
+            ParamRange::Values(_) => Err(Error::InvalidConfig(format!("no values for {}", name))),
+            ParamRange::Uniform(..) => Err(Error::InvalidConfig(format!(
+                "grid search needs explicit values for {}",
+                name
+            ))),
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R, name: &str) -> Result<f32> {
+        match self {
+            ParamRange::Values(values) if !values.is_empty() => {
+                Ok(values[rng.gen_range(0, values.len())])
+            }
+            ParamRange::Values(_) => Err(Error::InvalidConfig(format!("no values for {}", name))),
+            ParamRange::Uniform(low, high) if low < high => Ok(rng.gen_range(*low, *high)),
+            ParamRange::Uniform(low, _) => Ok(*low),
+        }
+    }
+}
+
+/// "0.1,0.2,0.5" lists values, "0.01..0.5" is a uniform range
+impl FromStr for ParamRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ParamRange> {
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f32>()
+                .map_err(|e| Error::InvalidConfig(format!("{:?} in {:?}: {}", v, s, e)))
+        };
+
+        match s.find("..") {
+            Some(i) => Ok(ParamRange::Uniform(parse(&s[..i])?, parse(&s[i + 2..])?)),
+            None => Ok(ParamRange::Values(
+                s.split(',').map(parse).collect::<Result<_>>()?,
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Params {
+    pub alpha: f32,
+    pub epsilon: f32,
+    pub gamma: f32,
+    pub initial_q: Reward,
+    pub n: usize, // n-step SARSA steps or DynaQ planning steps, unused otherwise
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchSpace {
+    pub alpha: ParamRange,
+    pub epsilon: ParamRange,
+    pub gamma: ParamRange,
+    pub initial_q: ParamRange,
+    pub n: ParamRange, // rounded to whole steps
+}
+
+impl SearchSpace {
+    /// Single point at the defaults used in main.rs
+    pub fn new() -> SearchSpace {
+        SearchSpace {
+            alpha: ParamRange::Values(vec![0.1]),
+            epsilon: ParamRange::Values(vec![0.05]),
+            gamma: ParamRange::Values(vec![0.8]),
+            initial_q: ParamRange::Values(vec![10.]),
+            n: ParamRange::Values(vec![7.]),
+        }
+    }
+
+    pub fn grid(&self) -> Result<Vec<Params>> {
+        let mut grid = Vec::new();
+
+        for &alpha in self.alpha.values("alpha")?.iter() {
+            for &epsilon in self.epsilon.values("epsilon")?.iter() {
+                for &gamma in self.gamma.values("gamma")?.iter() {
+                    for &initial_q in self.initial_q.values("initial_q")?.iter() {
+                        for &n in self.n.values("n")?.iter() {
+                            grid.push(Params {
+                                alpha,
+                                epsilon,
+                                gamma,
+                                initial_q,
+                                n: n.round().max(1.) as usize,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+
+    pub fn sample(&self, samples: usize, seed: u64) -> Result<Vec<Params>> {
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        (0..samples)
+            .map(|_| {
+                Ok(Params {
+                    alpha: self.alpha.sample(&mut rng, "alpha")?,
+                    epsilon: self.epsilon.sample(&mut rng, "epsilon")?,
+                    gamma: self.gamma.sample(&mut rng, "gamma")?,
+                    initial_q: self.initial_q.sample(&mut rng, "initial_q")?,
+                    n: self.n.sample(&mut rng, "n")?.round().max(1.) as usize,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for SearchSpace {
+    fn default() -> SearchSpace {
+        SearchSpace::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Search {
+    Grid,
+    Random { samples: usize, seed: u64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct SweepConfig {
+    pub algorithm: Algorithm,
+    pub space: SearchSpace,
+    pub search: Search,
+    pub seeds: Vec<u64>,      // one training run per seed for every configuration
+    pub episodes: usize,      // training episodes per run
+    pub eval_episodes: usize, // greedy episodes after training
+    pub max_steps: usize,     // step limit of every training and greedy episode
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepResult {
+    pub params: Params,
+    pub final_return: Reward, // mean greedy return after training
+    pub auc: Reward,          // mean training return, the normalized area under the learning curve
+    pub failures: usize,      // runs that ended in an error, left out of the means
+}
+
+/// Trains a learner from scratch, returning (final greedy return, area under the curve)
+fn run<E, F>(config: &SweepConfig, params: &Params, seed: u64, new: &F) -> Result<(Reward, Reward)>
+where
+    E: Environment + 'static,
+    E::Observation: Send,
+    E::Action: Send,
+    F: Fn() -> E,
+{
+    random::seed(seed);
+    let mut learner = config
+        .algorithm
+        .learner::<E>(params, new().get_terminal())?;
+    learner.config_mut().max_steps = Some(config.max_steps);
+
+    let mut total: Reward = 0.;
+    for _ in 0..config.episodes {
+        total += learner.episode(&mut new())?.gain;
+    }
+
+    let mut greedy: Reward = 0.;
+    for _ in 0..config.eval_episodes {
+        greedy += rollout(learner.as_ref(), &mut new(), config.max_steps)?;
+    }
+
+    Ok((
+        greedy / config.eval_episodes.max(1) as Reward,
+        total / config.episodes.max(1) as Reward,
+    ))
+}
+
+/// Return of the greedy policy over at most max_steps, leaving the learner untouched
+fn rollout<E: Environment>(
+    learner: &dyn TabularLearner<E>,
+    env: &mut E,
+    max_steps: usize,
+) -> Result<Reward> {
+    // env is preinitialized
+    let mut gain: Reward = 0.;
+    for _ in 0..max_steps {
+        if env.terminated() {
+            break;
+        }
+        let (action, _) = learner.greedy(env.current_observation(), env)?;
+        let (_, reward) = env.take_action(action)?;
+        gain += reward;
+    }
+    Ok(gain)
+}
+
+fn mean(values: &[Reward]) -> Reward {
+    if values.is_empty() {
+        Reward::NAN
+    } else {
+        values.iter().sum::<Reward>() / values.len() as Reward
+    }
+}
+
+// NaN (every run failed) ranks last
+fn descending(a: Reward, b: Reward) -> Ordering {
+    let key = |v: Reward| if v.is_nan() { Reward::NEG_INFINITY } else { v };
+    key(b).partial_cmp(&key(a)).unwrap_or(Ordering::Equal)
+}
+
+/// Runs every configuration and seed in parallel, best final greedy return first (ties broken
+/// by area under the curve). Results are reproducible for the same seeds
+pub fn sweep<E, F>(config: &SweepConfig, new: F) -> Result<Vec<SweepResult>>
+where
+    E: Environment + 'static,
+    E::Observation: Send,
+    E::Action: Send,
+    F: Fn() -> E + Sync,
+{
+    let configurations = match config.search {
+        Search::Grid => config.space.grid()?,
+        Search::Random { samples, seed } => config.space.sample(samples, seed)?,
+    };
+
+    let runs: Vec<(usize, Result<(Reward, Reward)>)> = configurations
+        .iter()
+        .enumerate()
+        .flat_map(|(i, params)| config.seeds.iter().map(move |seed| (i, params, *seed)))
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(i, params, seed)| (i, run(config, params, seed, &new)))
+        .collect();
+
+    let mut results: Vec<SweepResult> = configurations
+        .iter()
+        .enumerate()
+        .map(|(i, params)| {
+            let succeeded: Vec<(Reward, Reward)> = runs
+                .iter()
+                .filter(|(run, _)| *run == i)
+                .filter_map(|(_, result)| result.as_ref().ok().copied())
+                .collect();
+            let finals: Vec<Reward> = succeeded.iter().map(|(f, _)| *f).collect();
+            let aucs: Vec<Reward> = succeeded.iter().map(|(_, a)| *a).collect();
+
+            SweepResult {
+                params: *params,
+                final_return: mean(&finals),
+                auc: mean(&aucs),
+                failures: config.seeds.len() - succeeded.len(),
+            }
+        })
+        .collect();
+
+    rank(&mut results);
+    Ok(results)
+}
+
+/// Sorts results by final greedy return, best first, ties broken by area under the curve.
+/// Configurations where every run failed come last
+pub fn rank(results: &mut [SweepResult]) {
+    results
+        .sort_by(|a, b| descending(a.final_return, b.final_return).then(descending(a.auc, b.auc)));
+}
+
+/// Tab-separated table of ranked results, with the separate ranking by area under the curve
+pub fn write_table<W: Write>(results: &[SweepResult], mut writer: W) -> Result<()> {
+    let mut by_auc: Vec<usize> = (0..results.len()).collect();
+    by_auc.sort_by(|a, b| descending(results[*a].auc, results[*b].auc));
+    let mut auc_rank = vec![0; results.len()];
+    for (rank, i) in by_auc.into_iter().enumerate() {
+        auc_rank[i] = rank + 1;
+    }
+
+    writeln!(
+        writer,
+        "rank\tauc_rank\talpha\tepsilon\tgamma\tinitial_q\tn\tfinal_return\tauc\tfailures"
+    )?;
+    for (i, result) in results.iter().enumerate() {
+        let p = &result.params;
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}\t{:.3}\t{}",
+            i + 1,
+            auc_rank[i],
+            p.alpha,
+            p.epsilon,
+            p.gamma,
+            p.initial_q,
+            p.n,
+            result.final_return,
+            result.auc,
+            result.failures
+        )?;
+    }
+
+    Ok(())
+}
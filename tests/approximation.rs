@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use reinforcement_learning::approximation::{Features, LinearQ, TileCoding};
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+
+const TILINGS: usize = 8;
+
+// 4 tiles of 3 cells across, 1 cell high
+fn coding() -> TileCoding {
+    TileCoding::grid(TILINGS, 4, (12, 4)).unwrap()
+}
+
+fn active(coding: &TileCoding, cell: GridIndex) -> HashSet<usize> {
+    coding.active(cell).unwrap().into_iter().collect()
+}
+
+#[test]
+fn each_tiling_activates_one_feature() {
+    let coding = coding();
+    let len = Features::<GridIndex>::len(&coding);
+    let per_tiling = len / TILINGS;
+
+    for x in 0..12 {
+        for y in 0..4 {
+            let active = coding.active((x, y)).unwrap();
+            assert_eq!(active.len(), TILINGS);
+            for (tiling, feature) in active.iter().enumerate() {
+                assert!(
+                    (tiling * per_tiling..(tiling + 1) * per_tiling).contains(feature),
+                    "{:?}: {} in tiling {}",
+                    (x, y),
+                    feature,
+                    tiling
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn neighbouring_cells_share_tiles() {
+    let coding = coding();
+
+    let shared = active(&coding, (4, 1))
+        .intersection(&active(&coding, (5, 1)))
+        .count();
+    assert!(shared > 0 && shared < TILINGS, "{}", shared);
+    // tiles are a single cell high
+    assert_eq!(
+        active(&coding, (4, 1))
+            .intersection(&active(&coding, (4, 2)))
+            .count(),
+        0
+    );
+    assert_eq!(
+        active(&coding, (0, 0))
+            .intersection(&active(&coding, (11, 3)))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn semi_gradient_update_moves_value_towards_target() {
+    let mut q = LinearQ::<GridWorld, _>::new(coding());
+    let action = GridWorldAction::Up;
+
+    // alpha / tilings per active weight moves the value alpha of the way
+    let value = q.update(0.5 / TILINGS as f32, (4, 1), action, 10.).unwrap();
+    assert!((value - 5.).abs() < 1e-5);
+    assert!((q.value((4, 1), action).unwrap() - 5.).abs() < 1e-5);
+    let value = q.update(0.5 / TILINGS as f32, (4, 1), action, 10.).unwrap();
+    assert!((value - 7.5).abs() < 1e-5);
+
+    // generalizes to a neighbour in proportion to the shared tiles, not to other actions
+    let shared = active(&coding(), (4, 1))
+        .intersection(&active(&coding(), (5, 1)))
+        .count();
+    let neighbour = q.value((5, 1), action).unwrap();
+    assert!((neighbour - 7.5 * shared as f32 / TILINGS as f32).abs() < 1e-5);
+    assert_eq!(q.value((4, 1), GridWorldAction::Down).unwrap(), 0.);
+}
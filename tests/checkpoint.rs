@@ -0,0 +1,84 @@
+use std::env;
+use std::fs;
+
+use reinforcement_learning::checkpoint::{resume, train, Checkpointable, Decay, TrainingConfig};
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{
+    DynaQ, NStepSarsa, QLearning, Sarsa, TabularLearner, TabularLearnerConfig,
+};
+use reinforcement_learning::random;
+
+mod common;
+use common::{new_cliff, q_bits};
+
+const BEFORE: usize = 30; // episodes before the checkpoint
+const AFTER: usize = 20; // episodes after it, fewer so no later checkpoint overwrites it
+
+fn config() -> TabularLearnerConfig {
+    TabularLearnerConfig::new(0.5, 0.1, 0.9, 0.)
+}
+
+/// Trains BEFORE + AFTER episodes in one go, then resumes a fresh learner from the checkpoint
+/// written after BEFORE and trains the remaining AFTER. Both must end with the same Q table
+fn assert_resume_identical<L, F>(name: &str, new_learner: F)
+where
+    L: TabularLearner<GridWorld> + Checkpointable,
+    F: Fn() -> L,
+{
+    let path = env::temp_dir().join(format!(
+        "checkpoint_test_{}_{}.json",
+        name,
+        std::process::id()
+    ));
+    let config = TrainingConfig {
+        episodes: BEFORE + AFTER,
+        checkpoint_every: BEFORE,
+        decay: Some(Decay {
+            alpha: 0.9,
+            epsilon: 0.9,
+            every: 7,
+        }),
+    };
+
+    random::seed(0);
+    let mut uninterrupted = new_learner();
+    let trained = train(&mut uninterrupted, new_cliff, &config, &path).unwrap();
+    assert_eq!(trained.len(), BEFORE + AFTER);
+
+    random::seed(1); // resume must not depend on the generator it finds
+    let mut resumed = new_learner();
+    let remaining = resume(&mut resumed, new_cliff, &config, &path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(remaining.len(), AFTER);
+    assert_eq!(q_bits(&uninterrupted), q_bits(&resumed));
+    assert_eq!(uninterrupted.config().alpha, resumed.config().alpha);
+    assert_eq!(uninterrupted.config().epsilon, resumed.config().epsilon);
+}
+
+#[test]
+fn q_learning_resumes_identically() {
+    let terminal = new_cliff().get_terminal();
+    assert_resume_identical("q_learning", || QLearning::new(config(), terminal));
+}
+
+#[test]
+fn sarsa_resumes_identically() {
+    let terminal = new_cliff().get_terminal();
+    assert_resume_identical("sarsa", || Sarsa::new(config(), terminal));
+}
+
+#[test]
+fn n_step_sarsa_resumes_identically() {
+    let terminal = new_cliff().get_terminal();
+    assert_resume_identical("n_step_sarsa", || {
+        NStepSarsa::new(4, config(), terminal).unwrap()
+    });
+}
+
+#[test]
+fn dyna_q_resumes_identically() {
+    let terminal = new_cliff().get_terminal();
+    assert_resume_identical("dyna_q", || DynaQ::new(config(), 10, terminal));
+}
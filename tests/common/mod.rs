@@ -0,0 +1,73 @@
+//! Helpers shared by the integration tests, each of which only uses some of them
+#![allow(dead_code)]
+
+use reinforcement_learning::environment::gridworld::{
+    GridIndex, GridWorld, GridWorldAction, GridWorldDefinition,
+};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::{Environment, Reward, Transition};
+use reinforcement_learning::learner::{EpisodeStats, StepObserver, TabularLearner, ValueUpdate};
+
+pub fn new_cliff() -> GridWorld {
+    GridWorld::new(cliff(12, 4))
+}
+
+/// (0, 0) -> (1, 0) is the end, every other step costs 1
+pub fn corridor() -> GridWorld {
+    GridWorld::new(GridWorldDefinition::new((2, 1), (0, 0), (1, 0), -1.))
+}
+
+/// A single cell every action loops back to, paying reward per step
+pub fn constant_loop(reward: Reward) -> GridWorld {
+    GridWorld::new(GridWorldDefinition::new((1, 1), (0, 0), (1, 1), reward))
+}
+
+pub fn assert_close(actual: Reward, expected: Reward) {
+    assert!(
+        (actual - expected).abs() < 1e-5,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+/// Q table entries with values as bits, so the comparison is exact
+pub type QBits<S, A> = Vec<((S, A), u32)>;
+
+/// The Q table in key order
+pub fn q_bits<E, L>(learner: &L) -> QBits<E::Observation, E::Action>
+where
+    E: Environment,
+    L: TabularLearner<E> + ?Sized,
+{
+    let mut q: Vec<_> = learner
+        .data()
+        .q
+        .iter()
+        .map(|(key, value)| (key, value.to_bits()))
+        .collect();
+    q.sort();
+    q
+}
+
+/// Counts the events a learner reports
+#[derive(Default)]
+pub struct Counts {
+    pub steps: usize,
+    pub updates: usize,
+    pub episodes: usize,
+}
+
+impl StepObserver<GridWorld> for Counts {
+    fn on_step(&mut self, _transition: &Transition<GridIndex, GridWorldAction>) {
+        self.steps += 1;
+    }
+
+    fn on_update(&mut self, _update: &ValueUpdate<GridIndex, GridWorldAction>) {
+        self.updates += 1;
+    }
+
+    fn on_episode_end(&mut self, _stats: &EpisodeStats) {
+        self.episodes += 1;
+    }
+}
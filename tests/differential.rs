@@ -0,0 +1,99 @@
+use reinforcement_learning::environment::continuing::Continuing;
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::{Environment, Transition};
+use reinforcement_learning::learner::{
+    ContinuingLearner, DifferentialSarsa, RLearning, TabularLearnerConfig,
+};
+use reinforcement_learning::random;
+
+mod common;
+use common::constant_loop;
+
+const STEPS: usize = 5000;
+
+fn config() -> TabularLearnerConfig {
+    TabularLearnerConfig::new(0.1, 0.1, 1., 0.)
+}
+
+#[test]
+fn differential_sarsa_learns_the_average_reward() {
+    random::seed(0);
+    let mut env = Continuing::new(|| constant_loop(2.));
+    let mut learner = DifferentialSarsa::new(config(), 0.01, None);
+
+    let stats = learner.run(&mut env, STEPS).unwrap();
+    assert_eq!(stats.length, STEPS);
+    assert!(stats.truncated && !stats.terminated);
+    assert!(
+        (learner.average_reward() - 2.).abs() < 0.01,
+        "{}",
+        learner.average_reward()
+    );
+}
+
+#[test]
+fn r_learning_learns_the_average_reward() {
+    random::seed(0);
+    let mut env = Continuing::new(|| constant_loop(2.));
+    let mut learner = RLearning::new(config(), 0.01, None);
+
+    let stats = learner.run(&mut env, STEPS).unwrap();
+    assert_eq!(stats.gain, 2. * STEPS as f32);
+    assert!(
+        (learner.average_reward() - 2.).abs() < 0.01,
+        "{}",
+        learner.average_reward()
+    );
+}
+
+/// STEPS transitions of the loop, cycling through the actions
+fn trajectory() -> Vec<Transition<GridIndex, GridWorldAction>> {
+    let env = constant_loop(2.);
+    let actions = env.available_actions((0, 0));
+    (0..STEPS)
+        .map(|t| Transition {
+            state: (0, 0),
+            action: actions[t % actions.len()],
+            reward: 2.,
+            next_state: (0, 0),
+            terminal: false,
+        })
+        .collect()
+}
+
+/// learn_batch and replay move the average reward like `run` does
+fn assert_offline_learns_the_average_reward<L, F>(new: F)
+where
+    L: ContinuingLearner<GridWorld>,
+    F: Fn() -> L,
+{
+    let env = constant_loop(2.);
+    let trajectory = trajectory();
+
+    let mut batched = new();
+    for batch in trajectory.chunks(8) {
+        batched.learn_batch(batch, &env).unwrap();
+    }
+    let mut replayed = new();
+    replayed.replay(&trajectory, &env).unwrap();
+
+    for learner in [batched, replayed] {
+        assert!(
+            (learner.average_reward() - 2.).abs() < 0.01,
+            "{}",
+            learner.average_reward()
+        );
+    }
+}
+
+#[test]
+fn differential_sarsa_learns_the_average_reward_offline() {
+    random::seed(0);
+    assert_offline_learns_the_average_reward(|| DifferentialSarsa::new(config(), 0.01, None));
+}
+
+#[test]
+fn r_learning_learns_the_average_reward_offline() {
+    random::seed(0);
+    assert_offline_learns_the_average_reward(|| RLearning::new(config(), 0.01, None));
+}
@@ -0,0 +1,34 @@
+use reinforcement_learning::learner::EpisodeStats;
+
+#[test]
+fn rewards_are_discounted_by_their_step() {
+    let mut stats = EpisodeStats::new();
+    for reward in [1., 2., 4.].iter() {
+        stats.record(*reward, 0.5, false);
+    }
+
+    assert_eq!(stats.gain, 7.);
+    // 1 + 0.5 * 2 + 0.25 * 4
+    assert_eq!(stats.discounted_return, 3.);
+    assert_eq!(stats.length, 3);
+    assert!(!stats.terminated);
+}
+
+#[test]
+fn truncates_only_episodes_that_go_on() {
+    let mut stats = EpisodeStats::new();
+    stats.record(-1., 1., false);
+    assert!(!stats.truncate(Some(2)));
+    assert!(!stats.truncate(None));
+
+    let mut cut = stats;
+    cut.record(-1., 1., false);
+    assert!(cut.truncate(Some(2)));
+    assert!(cut.truncated && !cut.terminated);
+
+    // terminating on the last allowed step isn't a truncation
+    let mut finished = stats;
+    finished.record(-1., 1., true);
+    assert!(!finished.truncate(Some(2)));
+    assert!(finished.terminated && !finished.truncated);
+}
@@ -0,0 +1,127 @@
+use reinforcement_learning::environment::gridworld::GridWorldAction;
+use reinforcement_learning::environment::{Environment, Reward};
+use reinforcement_learning::learner::{
+    DynaQ, QLearning, TabularLearner, TabularLearnerConfig, TieBreaking,
+};
+use reinforcement_learning::{random, Error, Result};
+
+mod common;
+use common::corridor;
+
+/// A misconfigured environment offering no actions in its only, non-terminal, state
+struct Stuck;
+
+impl Environment for Stuck {
+    type Action = u8;
+    type Observation = u8;
+    type State = u8;
+
+    fn take_action(&mut self, _: u8) -> Result<(u8, Reward)> {
+        Ok((0, 0.))
+    }
+
+    fn available_actions(&self, _: u8) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn current_observation(&self) -> u8 {
+        0
+    }
+
+    fn current_state(&self) -> u8 {
+        0
+    }
+
+    fn terminated(&self) -> bool {
+        false
+    }
+
+    fn is_terminal(&self, _: u8) -> bool {
+        false
+    }
+
+    fn get_terminal(&self) -> Option<u8> {
+        None
+    }
+}
+
+// optimistic, so bootstrapping from the never-updated terminal would show up in the target
+fn config() -> TabularLearnerConfig {
+    TabularLearnerConfig::new(1., 0., 1., 10.)
+}
+
+#[test]
+fn q_learning_does_not_bootstrap_from_the_terminal() {
+    let env = corridor();
+    let mut learner = QLearning::new(config(), env.get_terminal());
+    learner.episode(&mut corridor()).unwrap();
+
+    let value = learner
+        .data()
+        .value(learner.config(), (0, 0), GridWorldAction::Right);
+    assert_eq!(value, 0.); // reaching the goal pays nothing
+}
+
+#[test]
+fn dyna_q_does_not_bootstrap_from_the_terminal() {
+    random::seed(0);
+    let env = corridor();
+    let mut learner = DynaQ::new(config(), 5, env.get_terminal());
+    learner.episode(&mut corridor()).unwrap();
+
+    let value = learner
+        .data()
+        .value(learner.config(), (0, 0), GridWorldAction::Right);
+    assert_eq!(value, 0.); // reaching the goal pays nothing
+}
+
+#[test]
+fn no_available_actions_is_an_error() {
+    for tie_breaking in [TieBreaking::First, TieBreaking::Random] {
+        let mut config = config();
+        config.tie_breaking = tie_breaking;
+        let learner = QLearning::new(config, None);
+
+        let no_actions = |result| matches!(result, Err(Error::NoAvailableActions(_)));
+        assert!(no_actions(learner.greedy(0, &Stuck).map(|_| ())));
+        assert!(no_actions(
+            learner.epsilon_greedy(0.5, 0, &Stuck).map(|_| ())
+        ));
+        assert!(no_actions(
+            learner
+                .epsilon_greedy_probability(0.5, 0, 0, &Stuck)
+                .map(|_| ())
+        ));
+        assert!(no_actions(
+            learner.epsilon_greedy_value(0.5, 0, &Stuck).map(|_| ())
+        ));
+    }
+}
+
+#[test]
+fn nan_values_are_an_error() {
+    let env = corridor();
+    for tie_breaking in [TieBreaking::First, TieBreaking::Random] {
+        let mut config = config();
+        config.tie_breaking = tie_breaking;
+        let mut learner = QLearning::new(config, env.get_terminal());
+        learner.update(1., (0, 0), GridWorldAction::Up, f32::NAN);
+
+        let invalid = |result| matches!(result, Err(Error::InvalidValue(_)));
+        assert!(invalid(learner.greedy((0, 0), &env).map(|_| ())));
+        assert!(invalid(learner.max_action_value((0, 0), &env).map(|_| ())));
+        assert!(invalid(
+            learner.epsilon_greedy(0., (0, 0), &env).map(|_| ())
+        ));
+        assert!(invalid(
+            learner
+                .epsilon_greedy_probability(0.5, (0, 0), GridWorldAction::Left, &env)
+                .map(|_| ())
+        ));
+        assert!(invalid(
+            learner.epsilon_greedy_value(0.5, (0, 0), &env).map(|_| ())
+        ));
+        // other states are unaffected
+        assert!(learner.greedy((1, 0), &env).is_ok());
+    }
+}
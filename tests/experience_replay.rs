@@ -0,0 +1,85 @@
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::{Environment, Transition};
+use reinforcement_learning::learner::experience_replay::{ReplayBuffer, Sampling};
+use reinforcement_learning::learner::{
+    ExperienceReplay, QLearning, TabularLearner, TabularLearnerConfig,
+};
+use reinforcement_learning::random;
+
+mod common;
+use common::Counts;
+
+const DRAWS: usize = 8000;
+
+fn transition(reward: f32) -> Transition<GridIndex, GridWorldAction> {
+    Transition {
+        state: (0, 0),
+        action: GridWorldAction::Up,
+        reward,
+        next_state: (0, 1),
+        terminal: false,
+    }
+}
+
+#[test]
+fn buffer_drops_the_oldest_transitions_once_full() {
+    let mut buffer = ReplayBuffer::new(3, Sampling::Uniform);
+    for reward in 0..5 {
+        buffer.push(transition(reward as f32));
+    }
+
+    assert_eq!(buffer.len(), 3);
+    let rewards: Vec<f32> = (0..3).map(|i| buffer.get(i).reward).collect();
+    assert_eq!(rewards, vec![2., 3., 4.]);
+
+    let mut empty = ReplayBuffer::new(0, Sampling::Uniform);
+    empty.push(transition(0.));
+    assert!(empty.is_empty());
+    assert_eq!(empty.sample(), None);
+}
+
+#[test]
+fn prioritized_sampling_follows_td_errors() {
+    random::seed(0);
+    let mut buffer = ReplayBuffer::new(3, Sampling::Prioritized { alpha: 1. });
+    for reward in 0..3 {
+        buffer.push(transition(reward as f32));
+    }
+    // the sign of the error doesn't matter, a zero error keeps a small priority
+    buffer.set_priority(0, -3.);
+    buffer.set_priority(1, 1.);
+    buffer.set_priority(2, 0.);
+
+    let mut counts = [0; 3];
+    for _ in 0..DRAWS {
+        counts[buffer.sample().unwrap()] += 1;
+    }
+
+    let frequency = |i: usize| counts[i] as f32 / DRAWS as f32;
+    assert!((frequency(0) - 0.75).abs() < 0.02, "{:?}", counts);
+    assert!((frequency(1) - 0.25).abs() < 0.02, "{:?}", counts);
+    assert!(counts[2] < DRAWS / 100, "{:?}", counts);
+}
+
+#[test]
+fn fractional_replay_ratios_carry_over() {
+    random::seed(0);
+    let new_cliff = || GridWorld::new(cliff(12, 4));
+    // greedy Left keeps the agent at the start for the whole episode
+    let mut config = TabularLearnerConfig::new(0.5, 0., 1., 0.);
+    config.max_steps = Some(5);
+    let learner = QLearning::new(config, new_cliff().get_terminal());
+    let mut replay = ExperienceReplay::new(learner, 100, 0.5, Sampling::Uniform);
+
+    // 2.5 replays owed, 2 made
+    let mut counts = Counts::default();
+    replay.episode_with(&mut new_cliff(), &mut counts).unwrap();
+    assert_eq!((counts.steps, counts.updates), (5, 5 + 2));
+
+    // the half left over makes it 3
+    let mut counts = Counts::default();
+    replay.episode_with(&mut new_cliff(), &mut counts).unwrap();
+    assert_eq!((counts.steps, counts.updates), (5, 5 + 3));
+    assert_eq!(replay.buffer().len(), 10);
+}
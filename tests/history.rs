@@ -0,0 +1,95 @@
+use reinforcement_learning::environment::history::{HistoryMode, HistoryWrapper, Window};
+use reinforcement_learning::environment::t_corridor::{
+    TCorridor, TCorridorAction, TCorridorConfig, TCorridorObservation,
+};
+use reinforcement_learning::environment::Environment;
+
+use TCorridorAction::{Down, Forward, Up};
+use TCorridorObservation::{Corridor, ObserveU, Split, Start};
+
+type Wrapped = HistoryWrapper<TCorridor, 2>;
+
+fn corridor(mode: HistoryMode) -> Wrapped {
+    let mut config = TCorridorConfig::new(1);
+    config.sideways = true;
+    config.upper_trap_probability = 1.;
+    HistoryWrapper::new(TCorridor::with_config(config), mode)
+}
+
+fn window(
+    observations: [Option<TCorridorObservation>; 2],
+    actions: [Option<TCorridorAction>; 2],
+) -> Window<TCorridorObservation, TCorridorAction, 2> {
+    Window {
+        observations,
+        actions,
+    }
+}
+
+#[test]
+fn window_keeps_the_last_steps_most_recent_first() {
+    let mut env = corridor(HistoryMode::Both);
+    assert_eq!(
+        env.current_observation().1,
+        window([None, None], [None, None])
+    );
+
+    let ((observation, w), _) = env.take_action(Down).unwrap();
+    assert_eq!(observation, Start);
+    assert_eq!(w, window([Some(Start), None], [Some(Down), None]));
+
+    let ((observation, w), _) = env.take_action(Forward).unwrap();
+    assert_eq!(observation, ObserveU);
+    assert_eq!(
+        w,
+        window([Some(Start), Some(Start)], [Some(Forward), Some(Down)])
+    );
+
+    // the oldest step drops out
+    let ((observation, w), _) = env.take_action(Forward).unwrap();
+    assert_eq!(observation, Corridor(1));
+    assert_eq!(
+        w,
+        window(
+            [Some(ObserveU), Some(Start)],
+            [Some(Forward), Some(Forward)]
+        )
+    );
+}
+
+#[test]
+fn modes_select_what_is_remembered() {
+    let mut observations = corridor(HistoryMode::Observations);
+    let mut actions = corridor(HistoryMode::Actions);
+    for action in [Down, Forward].iter() {
+        observations.take_action(*action).unwrap();
+        actions.take_action(*action).unwrap();
+    }
+
+    assert_eq!(
+        observations.current_observation().1,
+        window([Some(Start), Some(Start)], [None, None])
+    );
+    assert_eq!(
+        actions.current_observation().1,
+        window([None, None], [Some(Forward), Some(Down)])
+    );
+}
+
+#[test]
+fn window_is_cleared_at_the_end_of_an_episode() {
+    let mut env = corridor(HistoryMode::Both);
+    for action in [Forward, Forward, Forward].iter() {
+        env.take_action(*action).unwrap();
+    }
+    assert_eq!(env.current_observation().0, Split);
+
+    let (terminal, _) = env.take_action(Up).unwrap();
+    assert_eq!(terminal.1, window([None, None], [None, None]));
+    assert_eq!(env.get_terminal(), Some(terminal));
+    assert!(env.is_terminal(terminal));
+
+    // the next episode starts from an empty window too
+    let next = corridor(HistoryMode::Both);
+    assert_eq!(next.current_observation(), (Start, terminal.1));
+}
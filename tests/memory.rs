@@ -0,0 +1,157 @@
+use reinforcement_learning::environment::m_wrapper::{MAction, MWrapper, MWrapperConfig};
+use reinforcement_learning::environment::memory::{
+    Memory, Queue, QueueAction, Register, RegisterAction, Stack, StackAction,
+};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::Error;
+
+use reinforcement_learning::environment::gridworld::GridWorldAction::{Left, Right};
+
+mod common;
+use common::corridor;
+
+#[test]
+fn stack_pushes_and_pops_within_bounds() {
+    let mut stack = Stack::<2>::new(3);
+    assert!(!stack.actions(stack.contents()).contains(&StackAction::Pop));
+    assert!(!stack.apply(StackAction::Pop));
+
+    assert!(stack.apply(StackAction::Push(1)));
+    assert!(stack.apply(StackAction::Push(2)));
+    assert_eq!(stack.contents(), [Some(1), Some(2)]);
+
+    // full, only Noop and Pop are offered
+    assert_eq!(
+        stack.actions(stack.contents()),
+        vec![StackAction::Noop, StackAction::Pop]
+    );
+    assert!(!stack.apply(StackAction::Push(0)));
+    assert_eq!(stack.contents(), [Some(1), Some(2)]);
+
+    // last in, first out
+    assert!(stack.apply(StackAction::Pop));
+    assert_eq!(stack.contents(), [Some(1), None]);
+    assert!(stack.apply(StackAction::Pop));
+    assert!(!stack.apply(StackAction::Pop));
+    assert_eq!(stack.contents(), [None, None]);
+}
+
+#[test]
+fn queue_dequeues_in_order_and_rejects_overflow() {
+    let mut queue = Queue::<2>::new(3);
+    assert!(!queue.apply(QueueAction::Dequeue));
+
+    assert!(queue.apply(QueueAction::Enqueue(1)));
+    assert!(queue.apply(QueueAction::Enqueue(2)));
+    assert!(!queue.apply(QueueAction::Enqueue(0)));
+    assert!(!queue
+        .actions(queue.contents())
+        .contains(&QueueAction::Enqueue(0)));
+    assert_eq!(queue.contents(), [Some(1), Some(2)]);
+
+    // first in, first out
+    assert!(queue.apply(QueueAction::Dequeue));
+    assert_eq!(queue.contents(), [Some(2), None]);
+    assert!(queue.apply(QueueAction::Enqueue(0)));
+    assert_eq!(queue.contents(), [Some(2), Some(0)]);
+    assert!(queue.apply(QueueAction::Dequeue));
+    assert!(queue.apply(QueueAction::Dequeue));
+    assert!(!queue.apply(QueueAction::Dequeue));
+    assert_eq!(queue.contents(), [None, None]);
+}
+
+#[test]
+fn register_is_written_once() {
+    let mut register = Register::new(3);
+    assert_eq!(register.contents(), None);
+    assert_eq!(register.actions(None).len(), 1 + 3);
+
+    assert!(register.apply(RegisterAction::Set(2)));
+    assert_eq!(register.contents(), Some(2));
+    assert_eq!(register.actions(Some(2)), vec![RegisterAction::Noop]);
+
+    assert!(!register.apply(RegisterAction::Set(0)));
+    assert!(!register.apply(RegisterAction::Noop));
+    assert_eq!(register.contents(), Some(2));
+}
+
+#[test]
+fn wrapper_observes_the_memory_contents() {
+    let mut stack = MWrapper::with_memory(corridor(), Stack::<2>::new(2), MWrapperConfig::new());
+    assert_eq!(stack.current_observation(), ((0, 0), [None, None]));
+    let (observation, _) = stack
+        .take_action((Some(Left), StackAction::Push(1)))
+        .unwrap();
+    assert_eq!(observation, ((0, 0), [Some(1), None]));
+    // the terminal observation is the same whatever the memory holds
+    let (observation, _) = stack
+        .take_action((Some(Right), StackAction::Push(0)))
+        .unwrap();
+    assert_eq!(observation, ((1, 0), [None, None]));
+    assert_eq!(stack.get_terminal(), Some(observation));
+
+    let mut config = MWrapperConfig::new();
+    config.internal_actions = true;
+    let mut queue = MWrapper::with_memory(corridor(), Queue::<2>::new(2), config);
+    queue.take_action((None, QueueAction::Enqueue(1))).unwrap();
+    let (observation, _) = queue.take_action((None, QueueAction::Enqueue(0))).unwrap();
+    assert_eq!(observation, ((0, 0), [Some(1), Some(0)]));
+
+    let mut register = MWrapper::with_memory(corridor(), Register::new(2), MWrapperConfig::new());
+    let (observation, _) = register
+        .take_action((Some(Left), RegisterAction::Set(1)))
+        .unwrap();
+    assert_eq!(observation, ((0, 0), Some(1)));
+    assert!(register
+        .available_actions(observation)
+        .iter()
+        .all(|(_, action)| *action == RegisterAction::Noop));
+}
+
+#[test]
+fn wrapper_deducts_the_write_cost_on_writes_only() {
+    let mut config = MWrapperConfig::new();
+    config.write_cost = 0.5;
+    let mut env = MWrapper::with_config(corridor(), config);
+
+    assert_eq!(env.take_action((Some(Left), MAction::Noop)).unwrap().1, -1.);
+    assert_eq!(
+        env.take_action((Some(Left), MAction::Flip)).unwrap().1,
+        -1.5
+    );
+    // a write on the step reaching the goal still costs
+    assert_eq!(
+        env.take_action((Some(Right), MAction::Flip)).unwrap().1,
+        -0.5
+    );
+}
+
+#[test]
+fn wrapper_internal_steps_only_change_the_memory() {
+    let mut config = MWrapperConfig::new();
+    config.internal_actions = true;
+    config.internal_reward = -0.25;
+    config.write_cost = 0.5;
+    let mut env = MWrapper::with_config(corridor(), config);
+
+    let (observation, reward) = env.take_action((None, MAction::Flip)).unwrap();
+    assert_eq!(observation, ((0, 0), 1));
+    assert_eq!(reward, -0.75);
+    assert_eq!(env.current_state(), ((0, 0), 1));
+    assert!(!env.terminated());
+}
+
+#[test]
+fn wrapper_rejects_internal_steps_unless_enabled() {
+    let mut env = MWrapper::new(corridor());
+
+    assert!(!env
+        .available_actions(env.current_observation())
+        .iter()
+        .any(|(env_action, _)| env_action.is_none()));
+    assert!(matches!(
+        env.take_action((None, MAction::Flip)),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert_eq!(env.current_observation(), ((0, 0), 0));
+}
@@ -0,0 +1,100 @@
+use reinforcement_learning::environment::gridworld::{GridWorld, GridWorldAction};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{QLearning, TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::metrics::{greedy_regret, q_rmse, tracked_episode, ReferenceQ};
+
+use GridWorldAction::{Down, Left, Right, Up};
+
+mod common;
+use common::new_cliff;
+
+/// Q* (gamma = 1) of the start and the state above it: the shortest path from the start takes
+/// 13 steps, stepping into the cliff costs 100 and returns to the start
+fn reference() -> ReferenceQ<GridWorld> {
+    vec![
+        (((0, 0), Up), -13.),
+        (((0, 0), Right), -113.),
+        (((0, 0), Left), -14.),
+        (((0, 0), Down), -14.),
+        (((0, 1), Right), -12.),
+        (((0, 1), Up), -14.),
+        (((0, 1), Left), -13.),
+        (((0, 1), Down), -14.),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn learner() -> QLearning<GridWorld> {
+    QLearning::new(
+        TabularLearnerConfig::new(0.5, 0., 1., 0.),
+        new_cliff().get_terminal(),
+    )
+}
+
+#[test]
+fn q_rmse_is_zero_for_the_reference() {
+    let reference = reference();
+    let mut learner = learner();
+    for (&key, &value) in reference.iter() {
+        learner.data_mut().q.insert(key, value);
+    }
+
+    assert_eq!(q_rmse(&learner, &reference), 0.);
+}
+
+#[test]
+fn q_rmse_uses_initial_values_of_unseen_pairs() {
+    let reference = reference();
+    let mut learner = learner();
+    learner.data_mut().q.insert(((0, 0), Right), -100.);
+
+    // every other pair is still at initial_q = 0
+    let squared: f32 = reference
+        .iter()
+        .map(|(key, value)| match key {
+            ((0, 0), Right) => (-100f32 - value).powi(2),
+            _ => value.powi(2),
+        })
+        .sum();
+    let expected = (squared / reference.len() as f32).sqrt();
+
+    assert!((q_rmse(&learner, &reference) - expected).abs() < 1e-4);
+    assert_eq!(q_rmse(&learner, &ReferenceQ::<GridWorld>::new()), 0.);
+}
+
+#[test]
+fn greedy_regret_averages_over_reference_states() {
+    let reference = reference();
+    let env = new_cliff();
+    let mut learner = learner();
+
+    // greedy into the cliff from the start, optimal above it
+    for action in [Up, Left, Down].iter() {
+        learner.data_mut().q.insert(((0, 0), *action), -1.);
+    }
+    learner.data_mut().q.insert(((0, 1), Right), 1.);
+
+    let regret = greedy_regret(&learner, &reference, &env).unwrap();
+    assert!((regret - (-13. - -113.) / 2.).abs() < 1e-4);
+
+    learner.data_mut().q.insert(((0, 0), Up), 1.);
+    assert_eq!(greedy_regret(&learner, &reference, &env).unwrap(), 0.);
+}
+
+#[test]
+fn tracked_episode_measures_after_learning() {
+    let reference = reference();
+    let mut learner = learner();
+    learner.config_mut().max_steps = Some(50);
+    let mut env = new_cliff();
+
+    let metrics = tracked_episode(&mut learner, &mut env, &reference).unwrap();
+
+    assert!(metrics.stats.length > 0);
+    assert_eq!(metrics.q_rmse, q_rmse(&learner, &reference));
+    assert_eq!(
+        metrics.regret,
+        greedy_regret(&learner, &reference, &env).unwrap()
+    );
+}
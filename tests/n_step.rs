@@ -0,0 +1,203 @@
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::environment::Reward;
+use reinforcement_learning::learner::n_step::{Bootstrap, Step};
+use reinforcement_learning::learner::{
+    NStepLearner, NStepSarsa, OffPolicyNStepSarsa, QSigma, SigmaSchedule, StepObserver,
+    TabularLearner, TabularLearnerConfig, TreeBackup, ValueUpdate,
+};
+use reinforcement_learning::Error;
+
+use GridWorldAction::{Left, Right, Up};
+
+mod common;
+use common::{assert_close, new_cliff};
+
+const GAMMA: f32 = 0.9;
+
+fn config(epsilon: f32) -> TabularLearnerConfig {
+    TabularLearnerConfig::new(0.5, epsilon, GAMMA, 0.)
+}
+
+/// Q values along the trajectory (0, 1) -> (1, 1) -> (2, 1) -> (3, 1), Right is greedy in each
+fn set_q<L: TabularLearner<GridWorld>>(learner: &mut L) {
+    let q = &mut learner.data_mut().q;
+    q.insert(((1, 1), Right), 2.);
+    q.insert(((1, 1), Up), 1.);
+    q.insert(((2, 1), Right), 4.);
+    q.insert(((2, 1), Left), 3.);
+    q.insert(((3, 1), Right), 5.);
+    q.insert(((3, 1), Up), 1.);
+}
+
+/// Three steps with rewards -1, -2, -3, the second taking `second`, each with behaviour
+/// probability b. The episode goes on at (3, 1), taking Right
+fn fill_history<L: NStepLearner<GridWorld>>(
+    learner: &mut L,
+    second: GridWorldAction,
+    b: f32,
+) -> Option<Bootstrap<GridIndex, GridWorldAction>> {
+    let steps = [
+        ((0, 1), Right, -1.),
+        ((1, 1), second, -2.),
+        ((2, 1), Right, -3.),
+    ];
+    for &(state, action, reward) in steps.iter() {
+        learner.history_mut().push(Step {
+            state,
+            action,
+            reward,
+            probability: b,
+        });
+    }
+    Some(((3, 1), Right, b))
+}
+
+fn target<L: NStepLearner<GridWorld>>(
+    learner: &mut L,
+    second: GridWorldAction,
+    b: f32,
+) -> (Reward, f32) {
+    set_q(learner);
+    let bootstrap = fill_history(learner, second, b);
+    learner.n_step_target(bootstrap, &new_cliff()).unwrap()
+}
+
+#[test]
+fn constructors_reject_zero_steps() {
+    let terminal = new_cliff().get_terminal();
+
+    assert!(matches!(
+        NStepSarsa::<GridWorld>::new(0, config(0.1), terminal),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        TreeBackup::<GridWorld>::new(0, config(0.1), 0., terminal),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        QSigma::<GridWorld>::new(0, config(0.1), 0., SigmaSchedule::Constant(0.5), terminal),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        OffPolicyNStepSarsa::<GridWorld>::new(0, config(0.1), 0., terminal),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(NStepSarsa::<GridWorld>::new(1, config(0.1), terminal).is_ok());
+}
+
+#[test]
+fn sigma_one_is_n_step_sarsa_on_policy() {
+    let terminal = new_cliff().get_terminal();
+    // greedy behaviour and target policies, so every ratio is 1 and the expected values
+    // equal the values of the greedy actions taken
+    let mut sarsa = NStepSarsa::new(3, config(0.), terminal).unwrap();
+    let mut q_sigma =
+        QSigma::new(3, config(0.), 0., SigmaSchedule::Constant(1.), terminal).unwrap();
+
+    let expected = -1. + GAMMA * -2. + GAMMA.powi(2) * -3. + GAMMA.powi(3) * 5.;
+    let (sarsa_target, sarsa_ratio) = target(&mut sarsa, Right, 1.);
+    let (q_sigma_target, q_sigma_ratio) = target(&mut q_sigma, Right, 1.);
+
+    assert_close(sarsa_target, expected);
+    assert_close(q_sigma_target, expected);
+    assert_eq!((sarsa_ratio, q_sigma_ratio), (1., 1.));
+}
+
+#[test]
+fn sigma_zero_is_tree_backup() {
+    let terminal = new_cliff().get_terminal();
+    // target epsilon 0.2 over 4 actions: 0.85 for the greedy action, 0.05 for the others
+    let mut tree_backup = TreeBackup::new(3, config(0.1), 0.2, terminal).unwrap();
+    let mut q_sigma =
+        QSigma::new(3, config(0.1), 0.2, SigmaSchedule::Constant(0.), terminal).unwrap();
+
+    // expectation at the bootstrap state, then the untaken actions' values at each step back
+    let g3 = -3. + GAMMA * (0.05 * (5. + 1.) + 0.8 * 5.);
+    let g2 = -2. + GAMMA * 0.05 * 3. + GAMMA * 0.85 * g3;
+    let g1 = -1. + GAMMA * 0.05 * 1. + GAMMA * 0.85 * g2;
+
+    // behaviour probabilities don't matter without importance sampling
+    let (tree_backup_target, tree_backup_ratio) = target(&mut tree_backup, Right, 0.3);
+    let (q_sigma_target, _) = target(&mut q_sigma, Right, 0.7);
+
+    assert_close(tree_backup_target, g1);
+    assert_close(q_sigma_target, g1);
+    assert_eq!(tree_backup_ratio, 1.);
+}
+
+#[test]
+fn greedy_target_weights_by_the_behaviour_probabilities() {
+    let terminal = new_cliff().get_terminal();
+    let new = || OffPolicyNStepSarsa::new(3, config(0.1), 0., terminal).unwrap();
+
+    // A_1, A_2 and the bootstrap action are greedy, A_0 is given
+    let (greedy_target, greedy_ratio) = target(&mut new(), Right, 0.5);
+    assert_close(
+        greedy_target,
+        -1. + GAMMA * -2. + GAMMA.powi(2) * -3. + GAMMA.powi(3) * 5.,
+    );
+    assert_close(greedy_ratio, 1. / 0.5_f32.powi(3));
+
+    // the greedy policy never takes Left at (1, 1)
+    let (_, ratio) = target(&mut new(), Left, 0.5);
+    assert_eq!(ratio, 0.);
+}
+
+#[test]
+fn greedy_target_cuts_tree_backup_after_exploration() {
+    let terminal = new_cliff().get_terminal();
+    let mut tree_backup = TreeBackup::new(3, config(0.1), 0., terminal).unwrap();
+    let mut q_sigma =
+        QSigma::new(3, config(0.1), 0., SigmaSchedule::Constant(1.), terminal).unwrap();
+
+    // pi(Left | (1, 1)) = 0, so the return from (1, 1) on is replaced by max_a Q((1, 1), a)
+    let expected = -1. + GAMMA * 2.;
+
+    assert_close(target(&mut tree_backup, Left, 0.5).0, expected);
+    // rho = 0 zeroes the sampled correction just the same
+    assert_close(target(&mut q_sigma, Left, 0.5).0, expected);
+}
+
+#[derive(Default)]
+struct Targets(Vec<Reward>);
+
+impl StepObserver<GridWorld> for Targets {
+    fn on_update(&mut self, update: &ValueUpdate<GridIndex, GridWorldAction>) {
+        self.0.push(update.target);
+    }
+}
+
+fn truncated_targets<L: TabularLearner<GridWorld>>(mut learner: L) -> Vec<Reward> {
+    learner.config_mut().max_steps = Some(2);
+    let mut targets = Targets::default();
+    let stats = learner
+        .episode_with(&mut new_cliff(), &mut targets)
+        .unwrap();
+    assert!(stats.truncated);
+    targets.0
+}
+
+#[test]
+fn truncated_episodes_bootstrap_every_remaining_step() {
+    let terminal = new_cliff().get_terminal();
+    // alpha 0 keeps Q at initial_q = 10, greedy picks Left at the start, staying there at -1
+    let config = TabularLearnerConfig::new(0., 0., GAMMA, 10.);
+    // both steps are still in the history when the episode is cut
+    let expected = [-1. - GAMMA + GAMMA.powi(2) * 10., -1. + GAMMA * 10.];
+
+    let all = vec![
+        truncated_targets(NStepSarsa::new(3, config.clone(), terminal).unwrap()),
+        truncated_targets(TreeBackup::new(3, config.clone(), 0., terminal).unwrap()),
+        truncated_targets(
+            QSigma::new(3, config.clone(), 0., SigmaSchedule::Alternating, terminal).unwrap(),
+        ),
+        truncated_targets(OffPolicyNStepSarsa::new(3, config.clone(), 0., terminal).unwrap()),
+    ];
+    for targets in all {
+        assert_eq!(targets.len(), expected.len());
+        for (target, expected) in targets.iter().zip(expected.iter()) {
+            assert_close(*target, *expected);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{
+    DynaQ, NStepSarsa, QLearning, TabularLearner, TabularLearnerConfig, TreeBackup,
+};
+use reinforcement_learning::random;
+
+mod common;
+use common::{new_cliff, Counts};
+
+fn config() -> TabularLearnerConfig {
+    TabularLearnerConfig::new(0.5, 0.1, 1., 0.)
+}
+
+/// Events of one episode, run to the end
+fn episode_counts<L: TabularLearner<GridWorld>>(mut learner: L) -> Counts {
+    random::seed(0);
+    let mut counts = Counts::default();
+    let stats = learner.episode_with(&mut new_cliff(), &mut counts).unwrap();
+    assert!(stats.terminated);
+    assert_eq!(counts.steps, stats.length);
+    assert_eq!(counts.episodes, 1);
+    counts
+}
+
+#[test]
+fn q_learning_reports_an_update_per_step() {
+    let counts = episode_counts(QLearning::new(config(), new_cliff().get_terminal()));
+    assert_eq!(counts.updates, counts.steps);
+}
+
+#[test]
+fn dyna_q_reports_planning_updates() {
+    let n = 5;
+    let counts = episode_counts(DynaQ::new(config(), n, new_cliff().get_terminal()));
+    assert_eq!(counts.updates, counts.steps * (1 + n as usize));
+}
+
+// the last n steps are only updated once the episode has ended
+#[test]
+fn n_step_learners_report_end_of_episode_updates() {
+    let terminal = new_cliff().get_terminal();
+
+    let counts = episode_counts(NStepSarsa::new(4, config(), terminal).unwrap());
+    assert_eq!(counts.updates, counts.steps);
+    let counts = episode_counts(TreeBackup::new(4, config(), 0., terminal).unwrap());
+    assert_eq!(counts.updates, counts.steps);
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::{Environment, Reward, Transition};
+use reinforcement_learning::learner::policy::SoftmaxPolicy;
+use reinforcement_learning::learner::{
+    PolicyLearner, PolicyLearnerConfig, Reinforce, StepObserver,
+};
+use reinforcement_learning::random;
+
+use GridWorldAction::{Down, Left, Right, Up};
+
+mod common;
+use common::{assert_close, constant_loop};
+
+const ALPHA: f32 = 0.1;
+
+#[test]
+fn softmax_probabilities_sum_to_one() {
+    let env = constant_loop(0.);
+    let mut policy = SoftmaxPolicy::<GridWorld>::new();
+
+    let uniform = policy.probabilities((0, 0), &env).unwrap();
+    assert_eq!(uniform.len(), 4);
+    for (_, probability) in uniform {
+        assert_close(probability, 0.25);
+    }
+
+    policy.preferences.insert(((0, 0), Left), 1.);
+    policy.preferences.insert(((0, 0), Up), -1.);
+    let probabilities: HashMap<_, _> = policy
+        .probabilities((0, 0), &env)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let total = 1f32.exp() + 2. + (-1f32).exp();
+    assert_close(probabilities[&Left], 1f32.exp() / total);
+    assert_close(probabilities[&Right], 1. / total);
+    assert_close(probabilities[&Up], (-1f32).exp() / total);
+    assert_close(probabilities.values().sum(), 1.);
+}
+
+#[test]
+fn softmax_is_stable_for_large_preferences() {
+    let env = constant_loop(0.);
+    let mut policy = SoftmaxPolicy::<GridWorld>::new();
+    policy.preferences.insert(((0, 0), Left), 1000.);
+    policy.preferences.insert(((0, 0), Right), 999.);
+    policy.preferences.insert(((0, 0), Up), -1000.);
+    policy.preferences.insert(((0, 0), Down), -1000.);
+
+    let probabilities: HashMap<_, _> = policy
+        .probabilities((0, 0), &env)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(probabilities.values().all(|p| p.is_finite()));
+    assert_close(probabilities.values().sum(), 1.);
+    assert_close(probabilities[&Left] / probabilities[&Right], 1f32.exp());
+    assert_eq!(probabilities[&Up], 0.);
+}
+
+#[derive(Default)]
+struct Actions(Vec<GridWorldAction>);
+
+impl StepObserver<GridWorld> for Actions {
+    fn on_step(&mut self, transition: &Transition<GridIndex, GridWorldAction>) {
+        self.0.push(transition.action);
+    }
+}
+
+/// One-step episode, returning the action taken and every preference before and after
+fn one_step(
+    learner: &mut Reinforce<GridWorld>,
+    reward: Reward,
+) -> (GridWorldAction, Vec<(GridWorldAction, f32, f32)>) {
+    let env = constant_loop(reward);
+    let before = learner.policy().probabilities((0, 0), &env).unwrap();
+    let preferences = |learner: &Reinforce<GridWorld>| -> Vec<f32> {
+        before
+            .iter()
+            .map(|(action, _)| learner.policy().preference((0, 0), *action))
+            .collect()
+    };
+    let old = preferences(learner);
+
+    let mut actions = Actions::default();
+    learner
+        .episode_with(&mut constant_loop(reward), &mut actions)
+        .unwrap();
+    let new = preferences(learner);
+
+    let changes = before
+        .iter()
+        .zip(old.iter().zip(new.iter()))
+        .map(|((action, probability), (old, new))| (*action, *probability, new - old))
+        .collect();
+    (actions.0[0], changes)
+}
+
+fn learner(baseline: bool) -> Reinforce<GridWorld> {
+    let mut config = PolicyLearnerConfig::new(ALPHA, 0.5, 1.);
+    config.max_steps = Some(1);
+    Reinforce::new(config, baseline, constant_loop(0.).get_terminal())
+}
+
+/// h(s, b) moved by alpha * delta * (1[b = action] - pi(b|s))
+fn assert_step(
+    (taken, changes): (GridWorldAction, Vec<(GridWorldAction, f32, f32)>),
+    delta: Reward,
+) {
+    for (action, probability, change) in changes {
+        let indicator = if action == taken { 1. } else { 0. };
+        assert_close(change, ALPHA * delta * (indicator - probability));
+    }
+}
+
+#[test]
+fn reinforce_reinforces_actions_by_their_return() {
+    random::seed(0);
+
+    // positive returns make the taken action more likely, negative ones less
+    let mut positive = learner(false);
+    let (taken, changes) = one_step(&mut positive, 2.);
+    assert!(changes.iter().all(|(a, _, c)| (*a == taken) == (*c > 0.)));
+    assert_step((taken, changes), 2.);
+
+    let mut negative = learner(false);
+    let (taken, changes) = one_step(&mut negative, -2.);
+    assert!(changes.iter().all(|(a, _, c)| (*a == taken) == (*c < 0.)));
+    assert_step((taken, changes), -2.);
+}
+
+#[test]
+fn reinforce_baseline_is_subtracted_from_the_return() {
+    random::seed(0);
+    let mut learner = learner(true);
+
+    // the baseline starts at 0, then moves half way to the return of 2
+    assert_step(one_step(&mut learner, 2.), 2.);
+    assert_close(learner.baseline().unwrap().value((0, 0)), 1.);
+    // the truncated rest of the episode is estimated by the baseline: 2 + 1 - 1
+    assert_step(one_step(&mut learner, 2.), 2.);
+    assert_close(learner.baseline().unwrap().value((0, 0)), 2.);
+
+    // a return below the baseline makes the taken action less likely: -2 + 2 - 2
+    let (taken, changes) = one_step(&mut learner, -2.);
+    assert!(changes.iter().all(|(a, _, c)| (*a == taken) == (*c < 0.)));
+    assert_step((taken, changes), -2.);
+    assert_close(learner.baseline().unwrap().value((0, 0)), 1.);
+}
+
+#[test]
+fn reinforce_baseline_takes_the_terminal_from_the_env() {
+    random::seed(0);
+    let mut config = PolicyLearnerConfig::new(ALPHA, 0.5, 1.);
+    config.max_steps = Some(1);
+    let mut learner = Reinforce::new(config, true, None);
+
+    let mut env = constant_loop(0.);
+    learner.episode(&mut env).unwrap();
+    assert_eq!(
+        learner.baseline().unwrap().terminal_state,
+        env.get_terminal()
+    );
+}
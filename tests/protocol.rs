@@ -0,0 +1,103 @@
+use std::io::Cursor;
+
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorldAction};
+use reinforcement_learning::protocol::{serve, Response};
+
+mod common;
+use common::corridor;
+
+type GridResponse = Response<GridIndex, GridWorldAction>;
+
+/// Serves requests, one per line, returning the response to each
+fn exchange(requests: &[&str]) -> Vec<GridResponse> {
+    let mut output = Vec::new();
+    serve(corridor, Cursor::new(requests.join("\n")), &mut output).unwrap();
+
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn error_message(response: &GridResponse) -> &str {
+    match response {
+        Response::Error { message } => message,
+        response => panic!("expected an error, got {:?}", response),
+    }
+}
+
+#[test]
+fn malformed_requests_get_an_error_and_the_connection_stays_usable() {
+    let responses = exchange(&[
+        r#"{"command":"step","action":"#,
+        r#"{"command":"observation"}"#,
+    ]);
+
+    assert_eq!(responses.len(), 2);
+    assert!(error_message(&responses[0]).starts_with("malformed request"));
+    assert_eq!(
+        responses[1],
+        Response::Observation {
+            observation: (0, 0),
+            terminal: false
+        }
+    );
+}
+
+#[test]
+fn unknown_commands_and_actions_are_malformed() {
+    let responses = exchange(&[
+        r#"{"command":"jump"}"#,
+        r#"{"command":"step","action":"Jump"}"#,
+        r#"{"command":"step"}"#,
+    ]);
+
+    assert_eq!(responses.len(), 3);
+    for response in &responses {
+        assert!(error_message(response).starts_with("malformed request"));
+    }
+}
+
+#[test]
+fn stepping_after_the_end_is_an_error_until_reset() {
+    let responses = exchange(&[
+        r#"{"command":"step","action":"Right"}"#,
+        r#"{"command":"step","action":"Left"}"#,
+        r#"{"command":"reset"}"#,
+        r#"{"command":"step","action":"Left"}"#,
+    ]);
+
+    assert_eq!(
+        responses[0],
+        Response::Step {
+            observation: (1, 0),
+            reward: 0.,
+            terminal: true
+        }
+    );
+    assert_eq!(
+        error_message(&responses[1]),
+        "action taken in a terminal state"
+    );
+    assert_eq!(
+        responses[3],
+        Response::Step {
+            observation: (0, 0),
+            reward: -1.,
+            terminal: false
+        }
+    );
+}
+
+#[test]
+fn blank_lines_are_skipped() {
+    let responses = exchange(&["", r#"{"command":"terminal"}"#, "  "]);
+
+    assert_eq!(
+        responses,
+        vec![Response::Terminal {
+            observation: Some((1, 0))
+        }]
+    );
+}
@@ -0,0 +1,166 @@
+use rand::Rng;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use reinforcement_learning::environment::gridworld::GridWorld;
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::t_corridor::{
+    TCorridor, TCorridorConfig, TCorridorObservation,
+};
+use reinforcement_learning::environment::{Environment, Indexing};
+use reinforcement_learning::learner::{QLearning, QTable, TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::random;
+
+mod common;
+use common::{q_bits, QBits};
+
+/// Every index maps back to itself, so no two states or actions share one
+fn assert_bijective<S, A, I>(indexing: &I)
+where
+    S: Copy + Eq + Hash + Debug,
+    A: Copy + Eq + Hash + Debug,
+    I: Indexing<S, A>,
+{
+    for i in 0..indexing.states() {
+        assert_eq!(indexing.state_index(indexing.state(i)), i);
+    }
+    for i in 0..indexing.actions() {
+        assert_eq!(indexing.action_index(indexing.action(i)), i);
+    }
+}
+
+/// observation is covered and survives the round trip
+fn assert_round_trip<S, A, I>(indexing: &I, observation: S)
+where
+    S: Copy + Eq + Debug,
+    I: Indexing<S, A>,
+{
+    let index = indexing.state_index(observation);
+    assert!(index < indexing.states(), "{:?} -> {}", observation, index);
+    assert_eq!(indexing.state(index), observation);
+}
+
+/// Observations and actions seen over episodes of uniformly random actions
+fn explore<E: Environment, F: Fn() -> E>(
+    new: F,
+    episodes: usize,
+) -> (HashSet<E::Observation>, HashSet<E::Action>) {
+    let mut rng = random::rng();
+    let mut observations = HashSet::new();
+    let mut actions = HashSet::new();
+
+    for _ in 0..episodes {
+        let mut env = new();
+        observations.insert(env.current_observation());
+        for _ in 0..200 {
+            if env.terminated() {
+                break;
+            }
+            let available = env.available_actions(env.current_observation());
+            let action = available[rng.gen_range(0, available.len())];
+            actions.insert(action);
+            observations.insert(env.take_action(action).unwrap().0);
+        }
+    }
+
+    (observations, actions)
+}
+
+#[test]
+fn grid_indexing_round_trips_every_cell() {
+    let (width, height) = (12, 4);
+    let indexing = GridWorld::new(cliff(width, height)).indexing();
+    assert_eq!(indexing.states(), (width * height) as usize);
+    assert_bijective(&indexing);
+
+    for x in 0..width {
+        for y in 0..height {
+            assert_round_trip(&indexing, (x, y));
+        }
+    }
+}
+
+#[test]
+fn t_corridor_indexing_round_trips_every_observation() {
+    use TCorridorObservation::*;
+    let normal_steps = 5;
+    let indexing = TCorridor::new(normal_steps).indexing();
+    assert_bijective(&indexing);
+
+    let mut observations = vec![Start, ObserveU, ObserveL, Split, Terminal];
+    observations.extend((1..=normal_steps).map(Corridor));
+    assert_eq!(observations.len(), indexing.states());
+    for observation in observations {
+        assert_round_trip(&indexing, observation);
+    }
+}
+
+// the index of a longer corridor would be Split's
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "outside an indexing")]
+fn t_corridor_indexing_rejects_longer_corridors() {
+    let indexing = TCorridor::new(3).indexing();
+    indexing.state_index(TCorridorObservation::Corridor(4));
+}
+
+#[test]
+fn t_corridor_indexing_covers_random_lengths() {
+    random::seed(0);
+    let mut config = TCorridorConfig::new(4);
+    config.random_length = true;
+    config.backward = true;
+    config.sideways = true;
+    let indexing = TCorridor::with_config(config.clone()).indexing();
+
+    let (observations, actions) = explore(|| TCorridor::with_config(config.clone()), 200);
+    // the longest corridor was drawn, so every observation was seen
+    assert_eq!(observations.len(), indexing.states());
+    for observation in observations {
+        assert_round_trip(&indexing, observation);
+    }
+    for action in actions {
+        assert_eq!(indexing.action(indexing.action_index(action)), action);
+    }
+}
+
+fn trained<E, F>(
+    new: F,
+    dense: Option<QTable<E::Observation, E::Action>>,
+) -> QBits<E::Observation, E::Action>
+where
+    E: Environment,
+    F: Fn() -> E,
+{
+    random::seed(0);
+    let config = TabularLearnerConfig::new(0.5, 0.1, 0.9, 0.);
+    let mut learner = QLearning::new(config, new().get_terminal());
+    learner.config_mut().max_steps = Some(500);
+    if let Some(q) = dense {
+        learner.data_mut().q = q;
+    }
+
+    for _ in 0..50 {
+        learner.episode(&mut new()).unwrap();
+    }
+
+    q_bits(&learner)
+}
+
+#[test]
+fn dense_and_hash_map_tables_learn_the_same_values() {
+    let new_cliff = || GridWorld::new(cliff(12, 4));
+    let dense = QTable::dense(new_cliff().indexing());
+    assert_eq!(trained(new_cliff, None), trained(new_cliff, Some(dense)));
+
+    let mut config = TCorridorConfig::new(4);
+    config.random_length = true;
+    config.backward = true;
+    let new_corridor = || TCorridor::with_config(config.clone());
+    let dense = QTable::dense(new_corridor().indexing());
+    assert_eq!(
+        trained(new_corridor, None),
+        trained(new_corridor, Some(dense))
+    );
+}
@@ -0,0 +1,102 @@
+use std::env;
+use std::fs;
+
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::recorder::{
+    append_trajectory, load_trajectories, read_trajectories, write_trajectory, Recorder, Trajectory,
+};
+use reinforcement_learning::environment::t_corridor::{
+    TCorridor, TCorridorAction, TCorridorConfig, TCorridorObservation, TCorridorPosition,
+    TCorridorState, Trap,
+};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{QLearning, TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::random;
+
+mod common;
+use common::{new_cliff, q_bits};
+
+fn learner<E: Environment<Observation = GridIndex>>() -> QLearning<E> {
+    let mut config = TabularLearnerConfig::new(0.5, 0.1, 0.9, 0.);
+    config.max_steps = Some(200);
+    QLearning::new(config, new_cliff().get_terminal())
+}
+
+fn record(learner: &mut QLearning<Recorder<GridWorld>>) -> Trajectory<GridIndex, GridWorldAction> {
+    let mut recorder = Recorder::new(new_cliff());
+    let stats = learner.episode(&mut recorder).unwrap();
+    assert_eq!(recorder.trajectory().len(), stats.length);
+    assert_eq!(recorder.states().len(), stats.length);
+    recorder.into_trajectory()
+}
+
+#[test]
+fn trajectories_survive_writing_and_reading() {
+    random::seed(0);
+    let mut learner = learner();
+    let trajectories = vec![record(&mut learner), record(&mut learner)];
+
+    let mut buffer = Vec::new();
+    for trajectory in trajectories.iter() {
+        write_trajectory(&mut buffer, trajectory).unwrap();
+    }
+    let read: Vec<Trajectory<GridIndex, GridWorldAction>> =
+        read_trajectories(buffer.as_slice()).unwrap();
+    assert_eq!(read, trajectories);
+
+    let path = env::temp_dir().join(format!("recorder_test_{}.jsonl", std::process::id()));
+    for trajectory in trajectories.iter() {
+        append_trajectory(&path, trajectory).unwrap();
+    }
+    let loaded: Vec<Trajectory<GridIndex, GridWorldAction>> = load_trajectories(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, trajectories);
+}
+
+#[test]
+fn replaying_recorded_episodes_repeats_q_learning() {
+    random::seed(0);
+    let mut online = learner();
+    let trajectories: Vec<_> = (0..5).map(|_| record(&mut online)).collect();
+
+    let mut buffer = Vec::new();
+    for trajectory in trajectories.iter() {
+        write_trajectory(&mut buffer, trajectory).unwrap();
+    }
+
+    // the same one-step updates in the same order, without the environment
+    let mut offline = learner::<GridWorld>();
+    for trajectory in read_trajectories(buffer.as_slice()).unwrap() {
+        offline.replay(&trajectory, &new_cliff()).unwrap();
+    }
+
+    assert!(!offline.data().q.is_empty());
+    assert_eq!(q_bits(&online), q_bits(&offline));
+}
+
+#[test]
+fn recorder_keeps_the_states_the_agent_did_not_see() {
+    let mut config = TCorridorConfig::new(1);
+    config.upper_trap_probability = 0.;
+    let mut env = Recorder::new(TCorridor::with_config(config));
+    for _ in 0..3 {
+        env.take_action(TCorridorAction::Forward).unwrap();
+    }
+
+    use TCorridorObservation::{Corridor, ObserveL, Split};
+    let observations: Vec<_> = env.trajectory().iter().map(|t| t.next_state).collect();
+    assert_eq!(observations, [ObserveL, Corridor(1), Split]);
+
+    let state = |position| TCorridorState {
+        position,
+        trap: Trap::Lower,
+    };
+    assert_eq!(
+        env.states(),
+        [
+            state(TCorridorPosition::Cue),
+            state(TCorridorPosition::Corridor(1)),
+            state(TCorridorPosition::Split),
+        ]
+    );
+}
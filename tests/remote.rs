@@ -0,0 +1,181 @@
+use std::io::{self, BufRead, BufReader, Cursor};
+use std::net::TcpListener;
+use std::process::Command;
+use std::thread;
+
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::remote::Remote;
+use reinforcement_learning::environment::t_corridor::{TCorridorAction, TCorridorObservation};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{QLearning, TabularLearner, TabularLearnerConfig};
+use reinforcement_learning::protocol::serve;
+use reinforcement_learning::Error;
+
+type RemoteCliff = Remote<GridIndex, GridWorldAction>;
+
+/// Serves a single connection to a fresh cliff world in a background thread
+fn stub_cliff_server() -> RemoteCliff {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        serve(|| GridWorld::new(cliff(12, 4)), reader, stream).unwrap();
+    });
+
+    Remote::connect(address).unwrap()
+}
+
+#[test]
+fn mirrors_the_served_environment() {
+    let mut env = stub_cliff_server();
+
+    assert_eq!(env.current_observation(), (0, 0));
+    assert_eq!(env.get_terminal(), Some((11, 0)));
+    assert_eq!(env.available_actions((3, 2)).len(), 4);
+    assert_eq!(env.take_action(GridWorldAction::Up).unwrap(), ((0, 1), -1.));
+    assert_eq!(
+        env.take_action(GridWorldAction::Right).unwrap(),
+        ((1, 1), -1.)
+    );
+
+    env.reset().unwrap();
+    assert_eq!(env.current_observation(), (0, 0));
+    assert!(!env.terminated());
+}
+
+#[test]
+fn stepping_past_the_end_fails() {
+    let mut env = stub_cliff_server();
+
+    env.take_action(GridWorldAction::Up).unwrap();
+    for _ in 0..11 {
+        env.take_action(GridWorldAction::Right).unwrap();
+    }
+    let (observation, reward) = env.take_action(GridWorldAction::Down).unwrap();
+
+    assert_eq!((observation, reward), ((11, 0), 0.));
+    assert!(env.terminated());
+    assert!(matches!(
+        env.take_action(GridWorldAction::Up),
+        Err(Error::EpisodeTerminated)
+    ));
+}
+
+#[test]
+fn q_learning_trains_over_the_protocol() {
+    let mut env = stub_cliff_server();
+    let config = TabularLearnerConfig::new(0.5, 0.1, 1., 0.);
+    let mut learner = QLearning::new(config, env.get_terminal());
+
+    for _ in 0..300 {
+        env.reset().unwrap();
+        learner.episode(&mut env).unwrap();
+    }
+
+    learner.config_mut().epsilon = 0.;
+    env.reset().unwrap();
+    // optimal path along the cliff edge
+    assert_eq!(learner.episode(&mut env).unwrap().gain, -12.);
+}
+
+#[test]
+fn spawned_server_process() {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_env_server"));
+    command.args(["t_corridor", "--steps", "1"]);
+    let mut env: Remote<TCorridorObservation, TCorridorAction> =
+        Remote::spawn(&mut command).unwrap();
+
+    assert_eq!(env.current_observation(), TCorridorObservation::Start);
+    assert_eq!(env.get_terminal(), Some(TCorridorObservation::Terminal));
+    let (cue, _) = env.take_action(TCorridorAction::Forward).unwrap();
+    assert!(matches!(
+        cue,
+        TCorridorObservation::ObserveU | TCorridorObservation::ObserveL
+    ));
+}
+
+#[test]
+fn closed_connection_is_an_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    // hangs up after reading the first request
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+    });
+
+    let result: Result<RemoteCliff, _> = Remote::connect(address);
+    assert!(matches!(result, Err(Error::Remote(_))));
+}
+
+/// Answers a cliff world's requests with the given lines, then hangs up
+fn scripted(responses: &[&str]) -> Result<RemoteCliff, Error> {
+    let script = responses
+        .iter()
+        .map(|r| format!("{}\n", r))
+        .collect::<String>();
+    Remote::new(Cursor::new(script), io::sink())
+}
+
+const TERMINAL: &str = r#"{"type":"terminal","observation":[11,0]}"#;
+const RESET: &str = r#"{"type":"reset","observation":[0,0],"terminal":false}"#;
+const ALL_ACTIONS: &str = r#"{"type":"actions","actions":["Left","Right","Up","Down"]}"#;
+const STEP_UP: &str = r#"{"type":"step","observation":[0,1],"reward":-1.0,"terminal":false}"#;
+
+#[test]
+fn actions_of_reached_observations_are_prefetched() {
+    let mut env = scripted(&[
+        TERMINAL,
+        RESET,
+        ALL_ACTIONS,
+        STEP_UP,
+        r#"{"type":"actions","actions":["Up"]}"#,
+    ])
+    .unwrap();
+    env.take_action(GridWorldAction::Up).unwrap();
+
+    // served from the cache after the server has hung up
+    assert_eq!(env.available_actions((0, 1)), vec![GridWorldAction::Up]);
+    assert_eq!(env.available_actions((0, 0)).len(), 4);
+    assert!(env.take_error().is_none());
+}
+
+#[test]
+fn failed_action_requests_fail_the_next_step() {
+    let mut env = scripted(&[
+        TERMINAL,
+        RESET,
+        ALL_ACTIONS,
+        r#"{"type":"error","message":"unknown observation"}"#,
+        STEP_UP,
+        ALL_ACTIONS,
+    ])
+    .unwrap();
+
+    // never reached, so it has to be fetched
+    assert!(env.available_actions((3, 2)).is_empty());
+    match env.take_action(GridWorldAction::Up) {
+        Err(Error::Remote(message)) => assert_eq!(message, "unknown observation"),
+        result => panic!("expected the stored error, got {:?}", result),
+    }
+    // reported once, the connection stays usable
+    assert_eq!(env.take_action(GridWorldAction::Up).unwrap(), ((0, 1), -1.));
+}
+
+#[test]
+fn failing_to_prefetch_fails_the_step() {
+    let mut env = scripted(&[TERMINAL, RESET, ALL_ACTIONS, STEP_UP]).unwrap();
+    assert!(matches!(
+        env.take_action(GridWorldAction::Up),
+        Err(Error::Remote(_))
+    ));
+
+    // nor can a new episode start without the actions of its first observation
+    let result = scripted(&[TERMINAL, RESET]);
+    assert!(matches!(result, Err(Error::Remote(_))));
+}
@@ -0,0 +1,107 @@
+use reinforcement_learning::environment::Reward;
+use reinforcement_learning::sweep::{rank, ParamRange, Params, SearchSpace, SweepResult};
+use reinforcement_learning::Error;
+
+#[test]
+fn ranges_parse_from_lists_and_intervals() {
+    assert_eq!(
+        "0.1, 0.2,0.5".parse::<ParamRange>().unwrap(),
+        ParamRange::Values(vec![0.1, 0.2, 0.5])
+    );
+    assert_eq!(
+        "7".parse::<ParamRange>().unwrap(),
+        ParamRange::Values(vec![7.])
+    );
+    assert_eq!(
+        "0.01..0.5".parse::<ParamRange>().unwrap(),
+        ParamRange::Uniform(0.01, 0.5)
+    );
+    assert_eq!(
+        "-1 .. 1".parse::<ParamRange>().unwrap(),
+        ParamRange::Uniform(-1., 1.)
+    );
+
+    for invalid in ["", "0.1,", "a", "0.1..", "..0.5", "0.1..x"].iter() {
+        assert!(
+            matches!(invalid.parse::<ParamRange>(), Err(Error::InvalidConfig(_))),
+            "{:?}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn grid_is_the_cartesian_product() {
+    let space = SearchSpace {
+        alpha: ParamRange::Values(vec![0.1, 0.5]),
+        epsilon: ParamRange::Values(vec![0.05]),
+        gamma: ParamRange::Values(vec![0.9, 1.]),
+        initial_q: ParamRange::Values(vec![0.]),
+        n: ParamRange::Values(vec![2., 4., 8.]),
+    };
+
+    let grid = space.grid().unwrap();
+    assert_eq!(grid.len(), 2 * 2 * 3);
+    for &alpha in [0.1, 0.5].iter() {
+        for &gamma in [0.9, 1.].iter() {
+            for &n in [2, 4, 8].iter() {
+                let params = Params {
+                    alpha,
+                    epsilon: 0.05,
+                    gamma,
+                    initial_q: 0.,
+                    n,
+                };
+                assert_eq!(grid.iter().filter(|p| **p == params).count(), 1);
+            }
+        }
+    }
+
+    let mut uniform = space;
+    uniform.alpha = ParamRange::Uniform(0.1, 0.5);
+    assert!(matches!(uniform.grid(), Err(Error::InvalidConfig(_))));
+    uniform.alpha = ParamRange::Values(vec![]);
+    assert!(matches!(uniform.grid(), Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn n_is_rounded_to_at_least_one_step() {
+    let mut space = SearchSpace::new();
+    space.n = ParamRange::Values(vec![0., 0.4, 2.4, 2.6]);
+    let n: Vec<usize> = space.grid().unwrap().iter().map(|p| p.n).collect();
+    assert_eq!(n, vec![1, 1, 2, 3]);
+
+    space.n = ParamRange::Uniform(-3., 0.4);
+    let samples = space.sample(50, 0).unwrap();
+    assert!(samples.iter().all(|p| p.n == 1));
+
+    // sampling is reproducible
+    space.alpha = ParamRange::Uniform(0.01, 0.5);
+    assert_eq!(space.sample(20, 3).unwrap(), space.sample(20, 3).unwrap());
+}
+
+fn result(alpha: f32, final_return: Reward, auc: Reward) -> SweepResult {
+    let mut params = SearchSpace::new().grid().unwrap()[0];
+    params.alpha = alpha;
+    SweepResult {
+        params,
+        final_return,
+        auc,
+        failures: 0,
+    }
+}
+
+#[test]
+fn results_rank_by_final_return_then_auc() {
+    let mut results = vec![
+        result(0.1, -20., 5.),
+        result(0.2, Reward::NAN, Reward::NAN), // every run failed
+        result(0.3, 10., -50.),
+        result(0.4, 10., -30.),
+        result(0.5, 50., -90.),
+    ];
+    rank(&mut results);
+
+    let order: Vec<f32> = results.iter().map(|r| r.params.alpha).collect();
+    assert_eq!(order, vec![0.5, 0.4, 0.3, 0.1, 0.2]);
+}
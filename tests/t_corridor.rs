@@ -0,0 +1,71 @@
+use reinforcement_learning::environment::t_corridor::{
+    TCorridor, TCorridorAction, TCorridorConfig, TCorridorObservation, TCorridorPosition,
+    TCorridorState, Trap,
+};
+use reinforcement_learning::environment::Environment;
+
+use TCorridorAction::{Backward, Forward};
+use TCorridorObservation::{Corridor, ObserveL, ObserveU, Split, Start};
+
+fn corridor(normal_steps: usize) -> TCorridor {
+    let mut config = TCorridorConfig::new(normal_steps);
+    config.backward = true;
+    config.upper_trap_probability = 1.;
+    TCorridor::with_config(config)
+}
+
+fn walk(env: &mut TCorridor, action: TCorridorAction, expected: &[TCorridorObservation]) {
+    for observation in expected {
+        assert!(env
+            .available_actions(env.current_observation())
+            .contains(&action));
+        assert_eq!(env.take_action(action).unwrap().0, *observation);
+    }
+}
+
+#[test]
+fn backward_is_offered_from_the_cue_on() {
+    let mut env = corridor(2);
+
+    assert!(!env.available_actions(Start).contains(&Backward));
+    walk(
+        &mut env,
+        Forward,
+        &[ObserveU, Corridor(1), Corridor(2), Split],
+    );
+}
+
+#[test]
+fn backward_keeps_the_baseline_transitions() {
+    let mut env = corridor(2);
+
+    // the cue steps back to the start
+    walk(&mut env, Forward, &[ObserveU]);
+    walk(&mut env, Backward, &[Start]);
+
+    // the corridor stays put
+    walk(&mut env, Forward, &[ObserveU, Corridor(1), Corridor(2)]);
+    walk(&mut env, Backward, &[Corridor(2), Corridor(2)]);
+
+    // the split returns to the cue
+    walk(&mut env, Forward, &[Split]);
+    walk(&mut env, Backward, &[ObserveU]);
+}
+
+#[test]
+fn the_state_keeps_the_trap_the_observation_only_shows_at_the_cue() {
+    for (probability, trap, cue) in [(1., Trap::Upper, ObserveU), (0., Trap::Lower, ObserveL)] {
+        let mut config = TCorridorConfig::new(1);
+        config.upper_trap_probability = probability;
+        let mut env = TCorridor::with_config(config);
+
+        let state = |position| TCorridorState { position, trap };
+        assert_eq!(env.current_observation(), Start);
+        assert_eq!(env.current_state(), state(TCorridorPosition::Start));
+
+        walk(&mut env, Forward, &[cue, Corridor(1), Split]);
+        // the split looks the same whichever way the trap lies
+        assert_eq!(env.current_observation(), Split);
+        assert_eq!(env.current_state(), state(TCorridorPosition::Split));
+    }
+}
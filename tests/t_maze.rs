@@ -0,0 +1,122 @@
+use reinforcement_learning::environment::t_maze::{
+    CueCombination, TMaze, TMazeAction, TMazeConfig, TMazeObservation, MAX_CUES,
+};
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::{random, Error};
+
+fn config(exits: usize, cues: usize, combination: CueCombination) -> TMazeConfig {
+    let mut config = TMazeConfig::new(3);
+    config.exits = exits;
+    config.cues = cues;
+    config.combination = combination;
+    config
+}
+
+fn safe_exit(exits: usize, combination: CueCombination, cues: &[u8]) -> u8 {
+    TMaze::with_cues(config(exits, cues.len(), combination), cues)
+        .unwrap()
+        .safe_exit()
+}
+
+/// Observations up to and including the junction
+fn walk(maze: &mut TMaze) -> Vec<TMazeObservation> {
+    let mut observations = vec![maze.current_observation()];
+    while maze.current_observation() != TMazeObservation::Junction {
+        observations.push(maze.take_action(TMazeAction::Forward).unwrap().0);
+    }
+    observations
+}
+
+#[test]
+fn first_combination_ignores_later_cues() {
+    use CueCombination::First;
+    assert_eq!(safe_exit(3, First, &[2, 0, 1]), 2);
+    assert_eq!(safe_exit(3, First, &[0, 2, 2]), 0);
+    assert_eq!(safe_exit(2, First, &[1]), 1);
+}
+
+#[test]
+fn sum_combination_adds_cues_modulo_exits() {
+    use CueCombination::Sum;
+    assert_eq!(safe_exit(3, Sum, &[2, 2, 1]), 2);
+    assert_eq!(safe_exit(4, Sum, &[3, 3]), 2);
+    // XOR for two exits
+    assert_eq!(safe_exit(2, Sum, &[1, 1]), 0);
+    assert_eq!(safe_exit(2, Sum, &[1, 0, 1, 1]), 1);
+}
+
+#[test]
+fn cues_are_shown_in_order_and_only_the_safe_exit_pays() {
+    let cues = [1, 3, 0];
+    let mut maze = TMaze::with_cues(config(4, 3, CueCombination::Sum), &cues).unwrap();
+
+    let observations = walk(&mut maze);
+    assert_eq!(
+        observations[..4],
+        [
+            TMazeObservation::Start,
+            TMazeObservation::Cue(1),
+            TMazeObservation::Cue(3),
+            TMazeObservation::Cue(0),
+        ]
+    );
+    let exits = maze.available_actions(TMazeObservation::Junction);
+    assert_eq!(exits.len(), 4);
+
+    for exit in exits {
+        let mut maze = TMaze::with_cues(config(4, 3, CueCombination::Sum), &cues).unwrap();
+        walk(&mut maze);
+        let (observation, reward) = maze.take_action(exit).unwrap();
+        assert_eq!(observation, TMazeObservation::Terminal);
+        // (1 + 3 + 0) % 4
+        let expected = if exit == TMazeAction::Exit(0) {
+            100.
+        } else {
+            -100.
+        };
+        assert_eq!(reward, expected);
+    }
+}
+
+#[test]
+fn distractors_replace_corridor_observations() {
+    random::seed(0);
+    let mut distracted = config(2, 1, CueCombination::Sum);
+    distracted.distractors = 3;
+    distracted.distractor_probability = 1.;
+    let observations = walk(&mut TMaze::new(distracted).unwrap());
+    let corridor = &observations[2..observations.len() - 1];
+    assert_eq!(corridor.len(), 3);
+    for observation in corridor {
+        assert!(
+            matches!(observation, TMazeObservation::Distractor(d) if *d < 3),
+            "{:?}",
+            observation
+        );
+    }
+
+    let observations = walk(&mut TMaze::new(config(2, 1, CueCombination::Sum)).unwrap());
+    assert_eq!(
+        observations[2..observations.len() - 1],
+        [
+            TMazeObservation::Corridor(1),
+            TMazeObservation::Corridor(2),
+            TMazeObservation::Corridor(3),
+        ]
+    );
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    let invalid = |config: TMazeConfig| matches!(TMaze::new(config), Err(Error::InvalidConfig(_)));
+
+    assert!(invalid(config(1, 1, CueCombination::Sum)));
+    assert!(invalid(config(0, 1, CueCombination::First)));
+    assert!(invalid(config(2, 0, CueCombination::Sum)));
+    assert!(invalid(config(2, MAX_CUES + 1, CueCombination::Sum)));
+
+    let cues = |sequence: &[u8]| TMaze::with_cues(config(3, 2, CueCombination::Sum), sequence);
+    assert!(matches!(cues(&[0]), Err(Error::InvalidConfig(_))));
+    assert!(matches!(cues(&[0, 3]), Err(Error::InvalidConfig(_))));
+    assert!(cues(&[0, 2]).is_ok());
+}
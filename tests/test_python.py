@@ -0,0 +1,114 @@
+"""Tests of the Python bindings (`python` feature). Build the module with `maturin develop`,
+then run `python -m pytest tests/test_python.py` or `python -m unittest tests/test_python.py`"""
+import math
+import unittest
+
+import reinforcement_learning as rl
+
+CLIFF_ACTIONS = ["Left", "Right", "Up", "Down"]
+
+
+class ObservationsAndActions(unittest.TestCase):
+    def test_tuples_round_trip(self):
+        env = rl.GridWorld()
+        self.assertEqual(env.reset(), ((0, 0), {}))
+        self.assertEqual(env.available_actions(), CLIFF_ACTIONS)
+        self.assertEqual(env.step("Up"), ((0, 1), -1.0, False, False, {}))
+        # observations passed back in, as tuples or lists
+        self.assertEqual(env.available_actions((3, 2)), CLIFF_ACTIONS)
+        self.assertEqual(env.available_actions([3, 2]), CLIFF_ACTIONS)
+
+    def test_data_carrying_variants_are_dicts(self):
+        env = rl.TCorridor(normal_steps=2)
+        env.reset(seed=0)
+        cue, _, _, _, _ = env.step("Forward")
+        self.assertIn(cue, ["ObserveU", "ObserveL"])
+        observation, reward, terminated, truncated, _ = env.step("Forward")
+        self.assertEqual(observation, {"Corridor": 1})
+        self.assertEqual((reward, terminated, truncated), (-5.0, False, False))
+        self.assertEqual(env.available_actions({"Corridor": 1}), ["Forward"])
+
+    def test_the_state_holds_more_than_the_observation(self):
+        env = rl.TCorridor(normal_steps=0)
+        env.reset(seed=0)
+        env.step("Forward")
+        observation, _, _, _, _ = env.step("Forward")
+        self.assertEqual(observation, "Split")
+        state = env.state()
+        self.assertEqual(state["position"], "Split")
+        self.assertIn(state["trap"], ["Upper", "Lower"])
+
+    def test_nested_and_optional_values_round_trip(self):
+        env = rl.MWrapper(rl.GridWorld())
+        self.assertEqual(env.observation(), ((0, 0), 0))
+        actions = env.available_actions()
+        self.assertIn(("Up", "Flip"), actions)
+        # memory-only steps aren't offered by default
+        self.assertTrue(all(action is not None for action, _ in actions))
+        observation, _, _, _, _ = env.step(("Up", "Flip"))
+        self.assertEqual(observation, ((0, 1), 1))
+        self.assertEqual(env.available_actions(((0, 1), 1)), actions)
+
+    def test_unconvertible_values_are_rejected(self):
+        env = rl.GridWorld()
+        with self.assertRaises(ValueError):
+            env.step("Jump")
+        with self.assertRaises(ValueError):
+            env.step(math.nan)
+        with self.assertRaises(ValueError):
+            env.step(object())
+        # nothing was taken
+        self.assertEqual(env.observation(), (0, 0))
+
+
+class Truncation(unittest.TestCase):
+    def test_episodes_are_truncated_at_max_steps(self):
+        env = rl.GridWorld(max_steps=2)
+        self.assertFalse(env.step("Left")[3])
+        self.assertTrue(env.step("Left")[3])
+
+        env.reset()
+        self.assertFalse(env.step("Left")[3])
+        env.max_steps = None
+        self.assertFalse(env.step("Left")[3])
+
+    def test_terminating_on_the_last_step_is_not_truncation(self):
+        env = rl.TCorridor(normal_steps=0, max_steps=3)
+        env.step("Forward")
+        env.step("Forward")
+        _, _, terminated, truncated, _ = env.step("Up")
+        self.assertEqual((terminated, truncated), (True, False))
+        self.assertTrue(env.terminated)
+
+    def test_memory_wrappers_keep_max_steps(self):
+        env = rl.MWrapper(rl.GridWorld(max_steps=1))
+        self.assertEqual(env.max_steps, 1)
+        self.assertTrue(env.step(("Left", "Noop"))[3])
+
+
+class Learners(unittest.TestCase):
+    def test_training_and_acting(self):
+        env = rl.GridWorld()
+        learner = rl.QLearning(env, alpha=0.5, epsilon=0.1)
+        gains = learner.train(env, n=5)
+        self.assertEqual(len(gains), 5)
+        self.assertTrue(all(gain < 0 for gain in gains))
+
+        learner.epsilon = 0.0
+        self.assertIn(learner.act(env), CLIFF_ACTIONS)
+        self.assertIn(learner.act(env, (3, 2)), CLIFF_ACTIONS)
+        observations = {observation for observation, _, _ in learner.q()}
+        self.assertIn((0, 0), observations)
+
+    def test_learners_stay_with_their_kind_of_environment(self):
+        learner = rl.Sarsa(rl.GridWorld())
+        with self.assertRaises(ValueError):
+            learner.act(rl.TCorridor())
+
+    def test_invalid_configs_are_errors(self):
+        with self.assertRaises(RuntimeError):
+            rl.NStepSarsa(rl.GridWorld(), n=0)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::environment::gridworld::{GridWorld, GridWorldAction};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::Environment;
+use reinforcement_learning::learner::{
+    QLearning, TabularLearner, TabularLearnerConfig, TieBreaking,
+};
+use reinforcement_learning::random;
+
+const DRAWS: usize = 8000;
+
+fn learner(env: &GridWorld, epsilon: f32, tie_breaking: TieBreaking) -> QLearning<GridWorld> {
+    let mut config = TabularLearnerConfig::new(0.5, epsilon, 1., 0.);
+    config.tie_breaking = tie_breaking;
+    QLearning::new(config, env.get_terminal())
+}
+
+fn frequencies(learner: &QLearning<GridWorld>, env: &GridWorld) -> HashMap<GridWorldAction, f32> {
+    let mut counts = HashMap::new();
+    for _ in 0..DRAWS {
+        let action = learner
+            .epsilon_greedy(learner.config.epsilon, (0, 0), env)
+            .unwrap();
+        *counts.entry(action).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(action, count)| (action, count as f32 / DRAWS as f32))
+        .collect()
+}
+
+#[test]
+fn first_always_picks_the_first_available_action() {
+    random::seed(0);
+    let env = GridWorld::new(cliff(12, 4));
+    let learner = learner(&env, 0., TieBreaking::First);
+
+    let frequencies = frequencies(&learner, &env);
+    assert_eq!(frequencies.len(), 1);
+    assert_eq!(frequencies[&GridWorldAction::Left], 1.);
+}
+
+#[test]
+fn random_picks_tied_actions_uniformly() {
+    random::seed(0);
+    let env = GridWorld::new(cliff(12, 4));
+    let learner = learner(&env, 0., TieBreaking::Random);
+
+    let frequencies = frequencies(&learner, &env);
+    assert_eq!(frequencies.len(), 4);
+    for frequency in frequencies.values() {
+        assert!((frequency - 0.25).abs() < 0.02, "{:?}", frequencies);
+    }
+}
+
+#[test]
+fn random_only_picks_among_maximal_actions() {
+    random::seed(0);
+    let env = GridWorld::new(cliff(12, 4));
+    let mut learner = learner(&env, 0., TieBreaking::Random);
+    learner
+        .data_mut()
+        .q
+        .insert(((0, 0), GridWorldAction::Right), 1.);
+    learner
+        .data_mut()
+        .q
+        .insert(((0, 0), GridWorldAction::Down), 1.);
+    learner
+        .data_mut()
+        .q
+        .insert(((0, 0), GridWorldAction::Left), -1.);
+
+    let frequencies = frequencies(&learner, &env);
+    assert_eq!(frequencies.len(), 2);
+    for action in &[GridWorldAction::Right, GridWorldAction::Down] {
+        assert!(
+            (frequencies[action] - 0.5).abs() < 0.02,
+            "{:?}",
+            frequencies
+        );
+    }
+}
+
+#[test]
+fn probabilities_match_selection() {
+    random::seed(0);
+    let env = GridWorld::new(cliff(12, 4));
+    let mut learner = learner(&env, 0.2, TieBreaking::Random);
+    learner
+        .data_mut()
+        .q
+        .insert(((0, 0), GridWorldAction::Left), -1.);
+
+    let frequencies = frequencies(&learner, &env);
+    let mut total = 0.;
+    for action in env.available_actions((0, 0)) {
+        let probability = learner
+            .epsilon_greedy_probability(0.2, (0, 0), action, &env)
+            .unwrap();
+        let frequency = frequencies.get(&action).copied().unwrap_or(0.);
+        assert!((probability - frequency).abs() < 0.02, "{:?}", action);
+        total += probability;
+    }
+    assert!((total - 1.).abs() < 1e-6);
+}
@@ -0,0 +1,201 @@
+use reinforcement_learning::environment::gridworld::{GridIndex, GridWorld, GridWorldAction};
+use reinforcement_learning::environment::gridworld_definitions::cliff;
+use reinforcement_learning::environment::t_corridor::{
+    TCorridor, TCorridorAction, TCorridorObservation,
+};
+use reinforcement_learning::environment::vectorized::VecEnv;
+use reinforcement_learning::environment::{EnvTransition, Environment, Reward, Transition};
+use reinforcement_learning::learner::{
+    run_batched, DynaQ, EpisodeStats, NStepSarsa, OffPolicyNStepSarsa, QLearning, QSigma, Sarsa,
+    SigmaSchedule, TabularLearner, TabularLearnerConfig, TreeBackup,
+};
+use reinforcement_learning::{random, Error};
+
+use GridWorldAction::{Down, Left, Right, Up};
+
+mod common;
+use common::{assert_close, corridor, q_bits, QBits};
+
+fn config() -> TabularLearnerConfig {
+    TabularLearnerConfig::new(0.5, 0.2, 0.9, 0.)
+}
+
+#[test]
+fn finished_instances_are_reset_and_reported_once() {
+    let mut envs = VecEnv::new(2, corridor);
+
+    envs.step(&[Left, Left], 0.5, None).unwrap();
+    assert!(envs.take_finished().is_empty());
+
+    let transitions = envs.step(&[Left, Right], 0.5, None).unwrap();
+    assert!(!transitions[0].terminal);
+    assert!(transitions[1].terminal);
+    assert_eq!(transitions[1].next_state, (1, 0));
+    // the second instance is back at the start, the first one untouched
+    assert_eq!(envs.observations(), vec![(0, 0), (0, 0)]);
+    assert!(!envs.get(1).terminated());
+
+    // -1 for Left, then 0 into the end
+    let finished = envs.take_finished();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].gain, -1.);
+    assert_eq!(finished[0].length, 2);
+    assert!(finished[0].terminated && !finished[0].truncated);
+    assert!(envs.take_finished().is_empty());
+}
+
+#[test]
+fn instances_are_reset_at_the_step_limit() {
+    let mut envs = VecEnv::new(1, corridor);
+
+    for _ in 0..3 {
+        envs.step(&[Left], 0.5, Some(3)).unwrap();
+    }
+    assert_eq!(envs.observations(), vec![(0, 0)]);
+    let finished = envs.take_finished();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].gain, -3.);
+    assert_eq!(finished[0].discounted_return, -1. - 0.5 - 0.25);
+    assert!(finished[0].truncated && !finished[0].terminated);
+
+    // the limit counts from the reset
+    envs.step(&[Left], 0.5, Some(3)).unwrap();
+    assert!(envs.take_finished().is_empty());
+}
+
+#[test]
+fn action_count_must_match_instances() {
+    let mut envs = VecEnv::new(3, corridor);
+
+    assert!(matches!(
+        envs.step(&[Right, Right], 1., None),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        envs.step(&[Right; 4], 1., None),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(envs.take_finished().is_empty());
+}
+
+fn seeded_run(
+    seed: u64,
+) -> (
+    QBits<TCorridorObservation, TCorridorAction>,
+    Vec<EpisodeStats>,
+) {
+    random::seed(seed);
+    let mut envs = VecEnv::new(8, || TCorridor::new(4));
+    let mut learner = QLearning::new(config(), envs.get(0).get_terminal());
+    let finished = run_batched(&mut learner, &mut envs, 200).unwrap();
+    (q_bits(&learner), finished)
+}
+
+#[test]
+fn seeded_runs_match() {
+    let (q, finished) = seeded_run(0);
+    assert!(!finished.is_empty());
+    assert_eq!((q.clone(), finished.clone()), seeded_run(0));
+    assert_ne!((q, finished), seeded_run(1));
+}
+
+/// Consecutive transitions of different episodes, the last one terminal
+fn batch() -> Vec<EnvTransition<GridWorld>> {
+    let transition = |state, action, reward, next_state, terminal| Transition {
+        state,
+        action,
+        reward,
+        next_state,
+        terminal,
+    };
+    vec![
+        transition((0, 0), Up, -1., (0, 1), false),
+        transition((0, 1), Right, -1., (1, 1), false),
+        transition((11, 1), Down, -1., (11, 0), true),
+    ]
+}
+
+fn learn<L: TabularLearner<GridWorld>>(learner: &mut L) -> [Reward; 3] {
+    let env = GridWorld::new(cliff(12, 4));
+    learner.data_mut().q.insert(((0, 1), Right), 4.);
+    learner.learn_batch(&batch(), &env).unwrap();
+
+    let value = |state: GridIndex, action| learner.data().q.get(&(state, action)).copied();
+    [
+        value((0, 0), Up).unwrap(),
+        value((0, 1), Right).unwrap(),
+        value((11, 1), Down).unwrap(),
+    ]
+}
+
+#[test]
+fn q_learning_updates_each_transition_on_its_own() {
+    let terminal = GridWorld::new(cliff(12, 4)).get_terminal();
+    let mut learner = QLearning::new(config(), terminal);
+
+    let [first, second, third] = learn(&mut learner);
+    // max_a Q((0, 1), a) = 4 before the second transition lowers it
+    assert_close(first, 0.5 * (-1. + 0.9 * 4.));
+    // nothing is known about (1, 1) yet
+    assert_close(second, 4. + 0.5 * (-1. - 4.));
+    assert_close(third, -0.5); // towards the reward alone
+}
+
+#[test]
+fn sarsa_targets_the_expected_value_of_its_policy() {
+    let terminal = GridWorld::new(cliff(12, 4)).get_terminal();
+    let mut learner = Sarsa::new(config(), terminal);
+
+    let [first, second, third] = learn(&mut learner);
+    // epsilon 0.2 over 4 actions: 0.85 for the greedy Right, 0.05 for each of the others
+    assert_close(first, 0.5 * (-1. + 0.9 * 0.85 * 4.));
+    assert_close(second, 4. + 0.5 * (-1. - 4.));
+    assert_close(third, -0.5); // towards the reward alone
+}
+
+#[test]
+fn dyna_q_learns_and_plans_from_each_transition() {
+    let terminal = GridWorld::new(cliff(12, 4)).get_terminal();
+
+    // without planning it's Q-learning
+    let mut learner = DynaQ::new(config(), 0, terminal);
+    let [first, second, third] = learn(&mut learner);
+    assert_close(first, 0.5 * (-1. + 0.9 * 4.));
+    assert_close(second, 4. + 0.5 * (-1. - 4.));
+    assert_close(third, -0.5); // towards the reward alone
+
+    // a single remembered transition is planned from 3 times after the real update
+    let mut learner = DynaQ::new(config(), 3, terminal);
+    let env = GridWorld::new(cliff(12, 4));
+    learner.learn_batch(&batch()[2..], &env).unwrap();
+    assert_close(
+        learner.data().q.get(&((11, 1), Down)).copied().unwrap(),
+        0.5f32.powi(4) - 1.,
+    );
+}
+
+#[test]
+fn n_step_learners_reject_batches() {
+    let env = GridWorld::new(cliff(12, 4));
+    let terminal = env.get_terminal();
+    let mut learners: Vec<Box<dyn TabularLearner<GridWorld>>> = vec![
+        Box::new(NStepSarsa::new(3, config(), terminal).unwrap()),
+        Box::new(TreeBackup::new(3, config(), 0., terminal).unwrap()),
+        Box::new(QSigma::new(3, config(), 0., SigmaSchedule::Alternating, terminal).unwrap()),
+        Box::new(OffPolicyNStepSarsa::new(3, config(), 0., terminal).unwrap()),
+    ];
+
+    for learner in learners.iter_mut() {
+        assert!(matches!(
+            learner.learn_batch(&batch(), &env),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(learner.data().q.is_empty());
+
+        let mut envs = VecEnv::new(2, || GridWorld::new(cliff(12, 4)));
+        assert!(matches!(
+            run_batched(learner.as_mut(), &mut envs, 1),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+}